@@ -0,0 +1,262 @@
+//! Builds a bloom object from newline delimited items, and writes it as a DUMP payload, or as commands which
+//! load it that can be piped into `valkey-cli --pipe`.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+use valkey_bloom_core::configs::{
+    BLOOM_CAPACITY_DEFAULT, BLOOM_CAPACITY_MAX, BLOOM_CAPACITY_MIN, BLOOM_EXPANSION_DEFAULT,
+    BLOOM_EXPANSION_MAX, BLOOM_EXPANSION_MIN, BLOOM_FP_RATE_DEFAULT, BLOOM_FP_RATE_MAX,
+    BLOOM_FP_RATE_MIN,
+};
+use valkey_bloom_core::rdb;
+use valkey_bloom_core::utils::{BloomFilterLayout, BloomFilterType, BloomHashFunction};
+
+const USAGE: &str = "Usage: valkey-bloom-build [OPTIONS] [FILE]
+
+Reads newline delimited items from FILE (or stdin), adds them to a bloom object and writes the object to stdout.
+Empty lines are skipped.
+
+Options:
+  --error-rate <rate>     False positive rate of the object (default 0.001)
+  --capacity <capacity>   Capacity of the first filter of the object (default 100000)
+  --expansion <rate>      Expansion rate of the object when it scales out (default 2)
+  --nonscaling            Do not scale out, so adding items fails once the capacity is reached
+  --layout <layout>       Layout of the filters: standard, blocked or redisbloom (default standard)
+  --hash <hash>           Hash function of the object: siphash, xxhash3, murmurhash3, murmurhash64a or
+                          murmurhash2 (default siphash)
+  --format <format>       Output format (default dump):
+                            dump       DUMP payload, e.g. for valkey-cli -x RESTORE <key> 0 < payload
+                            restore    RESTORE command to pipe into valkey-cli --pipe
+                            loadchunk  BF.LOADCHUNK commands to pipe into valkey-cli --pipe, for objects larger
+                                       than the maximum size of a RESTORE payload
+  --key <key>             Key of the restore and loadchunk commands
+  --replace               Replace an existing key with the restore and loadchunk commands
+  --output <file>         Write the output to the file instead of stdout
+  --help                  Print this help";
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Dump,
+    Restore,
+    LoadChunk,
+}
+
+struct BuildOptions {
+    fp_rate: f32,
+    capacity: u64,
+    expansion: u32,
+    layout: BloomFilterLayout,
+    hash_function: BloomHashFunction,
+    format: OutputFormat,
+    key: Option<String>,
+    replace: bool,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+/// Parse the command line arguments, returning None when the usage was requested.
+fn parse_options(args: &[String]) -> Result<Option<BuildOptions>, String> {
+    let mut options = BuildOptions {
+        fp_rate: BLOOM_FP_RATE_DEFAULT,
+        capacity: BLOOM_CAPACITY_DEFAULT as u64,
+        expansion: BLOOM_EXPANSION_DEFAULT as u32,
+        layout: BloomFilterLayout::Standard,
+        hash_function: BloomHashFunction::SipHash,
+        format: OutputFormat::Dump,
+        key: None,
+        replace: false,
+        input: None,
+        output: None,
+    };
+    let mut nonscaling = false;
+    let mut idx = 0;
+    while idx < args.len() {
+        let arg = args[idx].as_str();
+        idx += 1;
+        let mut value = || {
+            idx += 1;
+            args.get(idx - 1)
+                .map(String::as_str)
+                .ok_or(format!("missing value of {}", arg))
+        };
+        match arg {
+            "--help" | "-h" => return Ok(None),
+            "--error-rate" => {
+                options.fp_rate = match value()?.parse::<f32>() {
+                    Ok(num) if num > BLOOM_FP_RATE_MIN && num < BLOOM_FP_RATE_MAX => num,
+                    _ => return Err("bad error rate, it must be between 0 and 1".to_string()),
+                };
+            }
+            "--capacity" => {
+                options.capacity = match value()?.parse::<u64>() {
+                    Ok(num) if (BLOOM_CAPACITY_MIN..=BLOOM_CAPACITY_MAX).contains(&num) => num,
+                    _ => return Err("bad capacity, it must be larger than 0".to_string()),
+                };
+            }
+            "--expansion" => {
+                options.expansion = match value()?.parse::<u32>() {
+                    Ok(num) if (BLOOM_EXPANSION_MIN..=BLOOM_EXPANSION_MAX).contains(&num) => num,
+                    _ => {
+                        return Err(format!(
+                            "bad expansion, it must be between {} and {}",
+                            BLOOM_EXPANSION_MIN, BLOOM_EXPANSION_MAX
+                        ))
+                    }
+                };
+            }
+            "--nonscaling" => nonscaling = true,
+            "--layout" => {
+                let name = value()?;
+                options.layout =
+                    BloomFilterLayout::from_name(name).ok_or(format!("bad layout {}", name))?;
+            }
+            "--hash" => {
+                let name = value()?;
+                options.hash_function = BloomHashFunction::from_name(name)
+                    .ok_or(format!("bad hash function {}", name))?;
+            }
+            "--format" => {
+                options.format = match value()?.to_lowercase().as_str() {
+                    "dump" => OutputFormat::Dump,
+                    "restore" => OutputFormat::Restore,
+                    "loadchunk" => OutputFormat::LoadChunk,
+                    format => return Err(format!("bad format {}", format)),
+                };
+            }
+            "--key" => options.key = Some(value()?.to_string()),
+            "--replace" => options.replace = true,
+            "--output" => options.output = Some(value()?.to_string()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if options.input.is_none() => options.input = Some(arg.to_string()),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+    if nonscaling {
+        options.expansion = 0;
+    }
+    if options.format != OutputFormat::Dump && options.key.is_none() {
+        return Err("--key is needed by the restore and loadchunk formats".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// Returns a bloom object holding every non empty line of the input as an item.
+fn build_object(options: &BuildOptions, input: impl BufRead) -> Result<BloomFilterType, String> {
+    let mut bf = BloomFilterType::new_reserved(
+        options.fp_rate,
+        options.capacity,
+        options.expansion,
+        options.layout,
+        options.hash_function,
+    )
+    .map_err(|err| format!("failed to create the object: {}", err.as_str()))?;
+    for (line_idx, line) in input.split(b'\n').enumerate() {
+        let line = line.map_err(|err| format!("failed to read the input: {}", err))?;
+        let item = line.strip_suffix(b"\r").unwrap_or(&line);
+        if item.is_empty() {
+            continue;
+        }
+        bf.add_item(item).map_err(|err| {
+            format!(
+                "failed to add the item of line {}: {}",
+                line_idx + 1,
+                err.as_str()
+            )
+        })?;
+    }
+    Ok(bf)
+}
+
+/// Write a command in the RESP protocol, which `valkey-cli --pipe` sends as is.
+fn write_command(output: &mut impl Write, args: &[&[u8]]) -> io::Result<()> {
+    write!(output, "*{}\r\n", args.len())?;
+    for arg in args {
+        write!(output, "${}\r\n", arg.len())?;
+        output.write_all(arg)?;
+        output.write_all(b"\r\n")?;
+    }
+    Ok(())
+}
+
+/// Write the bloom object in the requested format.
+fn write_object(
+    options: &BuildOptions,
+    bf: &BloomFilterType,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let key = options.key.as_deref().unwrap_or_default().as_bytes();
+    match options.format {
+        OutputFormat::Dump => output.write_all(&rdb::dump_payload(bf))?,
+        OutputFormat::Restore => {
+            let payload = rdb::dump_payload(bf);
+            let mut args: Vec<&[u8]> = vec![b"RESTORE", key, b"0", &payload];
+            if options.replace {
+                args.push(b"REPLACE");
+            }
+            write_command(output, &args)?;
+        }
+        OutputFormat::LoadChunk => {
+            // The header creates the object, which is filled by the chunks of its bitmaps like BF.SCANDUMP replies them.
+            if options.replace {
+                write_command(output, &[b"UNLINK", key])?;
+            }
+            for (iterator, data) in bf.load_chunks() {
+                let iterator_arg = iterator.to_string();
+                write_command(
+                    output,
+                    &[b"BF.LOADCHUNK", key, iterator_arg.as_bytes(), &data],
+                )?;
+            }
+        }
+    }
+    output.flush()
+}
+
+fn run(options: &BuildOptions) -> Result<(), String> {
+    let bf = match &options.input {
+        Some(path) => {
+            let file =
+                File::open(path).map_err(|err| format!("failed to open {}: {}", path, err))?;
+            build_object(options, BufReader::new(file))?
+        }
+        None => build_object(options, io::stdin().lock())?,
+    };
+    let written = match &options.output {
+        Some(path) => {
+            let file =
+                File::create(path).map_err(|err| format!("failed to create {}: {}", path, err))?;
+            write_object(options, &bf, &mut BufWriter::new(file))
+        }
+        None => write_object(options, &bf, &mut BufWriter::new(io::stdout().lock())),
+    };
+    written.map_err(|err| format!("failed to write the output: {}", err))?;
+    eprintln!(
+        "Added {} items to {} filters using {} bytes",
+        bf.cardinality(),
+        bf.filters.len(),
+        bf.memory_usage()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_options(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("valkey-bloom-build: {}\n\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("valkey-bloom-build: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
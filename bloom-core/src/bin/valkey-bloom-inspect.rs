@@ -0,0 +1,497 @@
+//! Decodes the bloom objects of DUMP payloads or RDB files with the same logic the module loads them with, and
+//! prints their filters along with the reasons they cannot be loaded.
+use std::io::{self, Read};
+use std::process::ExitCode;
+use valkey_bloom_core::rdb::{
+    self, DumpPayloadReader, RdbReader, BLOOM_FILTER_TYPE_ENCODING_VERSION, BLOOM_FILTER_TYPE_NAME,
+    RDB_TYPE_MODULE_2, REDISBLOOM_FILTER_TYPE_NAME,
+};
+use valkey_bloom_core::utils::{BloomBitmap, BloomFilterType, BloomHashFunction, BLOCK_BITS};
+
+const USAGE: &str = "Usage: valkey-bloom-inspect [FILE]
+
+Prints the bloom objects of a DUMP payload or an RDB file read from FILE (or stdin), their filters and the reasons
+they cannot be loaded. RDB files are recognized by their header, anything else is decoded as a DUMP payload, e.g.
+  valkey-cli --raw DUMP key | head -c -1 > payload
+Exits with 1 when any bloom object cannot be loaded or fails validation.";
+
+/// Length of the magic string and RDB version at the start of RDB files, e.g. REDIS0011 or VALKEY080.
+const RDB_HEADER_LEN: usize = 9;
+
+/// RDB opcodes of the entries of RDB files which are not keys.
+const RDB_OPCODE_SLOT_INFO: u8 = 244;
+const RDB_OPCODE_FUNCTION2: u8 = 245;
+const RDB_OPCODE_MODULE_AUX: u8 = 247;
+const RDB_OPCODE_IDLE: u8 = 248;
+const RDB_OPCODE_FREQ: u8 = 249;
+const RDB_OPCODE_AUX: u8 = 250;
+const RDB_OPCODE_RESIZEDB: u8 = 251;
+const RDB_OPCODE_EXPIRETIME_MS: u8 = 252;
+const RDB_OPCODE_EXPIRETIME: u8 = 253;
+const RDB_OPCODE_SELECTDB: u8 = 254;
+const RDB_OPCODE_EOF: u8 = 255;
+
+/// Reads the values of a bloom object, keeping the number of bytes of its bitmaps to validate them.
+struct InspectingReader<'a, 'b> {
+    reader: &'a mut DumpPayloadReader<'b>,
+    bitmap_lens: Vec<usize>,
+}
+
+impl RdbReader for InspectingReader<'_, '_> {
+    type Buffer = Vec<u8>;
+
+    fn load_unsigned(&mut self) -> Option<u64> {
+        self.reader.load_unsigned()
+    }
+
+    fn load_float(&mut self) -> Option<f32> {
+        self.reader.load_float()
+    }
+
+    fn load_double(&mut self) -> Option<f64> {
+        self.reader.load_double()
+    }
+
+    fn load_string_buffer(&mut self) -> Option<Vec<u8>> {
+        let bitmap = self.reader.load_string_buffer()?;
+        self.bitmap_lens.push(bitmap.len());
+        Some(bitmap)
+    }
+}
+
+/// Counts of what was inspected, to summarize it.
+#[derive(Default)]
+struct Inspection {
+    num_keys: u64,
+    num_objects: u64,
+    num_invalid_objects: u64,
+}
+
+/// Returns the reasons the bloom object fails validation, with the number of bytes of the bitmaps it was loaded from.
+fn validate_object(bf: &BloomFilterType, bitmap_lens: &[usize]) -> Vec<String> {
+    let mut errors = Vec::new();
+    if bf.filters.is_empty() {
+        errors.push("the object holds no filters".to_string());
+    }
+    for (idx, (filter, bitmap_len)) in bf.filters.iter().zip(bitmap_lens).enumerate() {
+        let number_of_bits = filter.bloom.number_of_bits();
+        if number_of_bits == 0 {
+            errors.push(format!("filter {} has no bits", idx));
+        }
+        if filter.bloom.number_of_hash_functions() == 0 {
+            errors.push(format!("filter {} has no hash functions", idx));
+        }
+        let (expected_len, exact) = match &filter.bloom {
+            BloomBitmap::Blocked(_) => {
+                if number_of_bits % BLOCK_BITS != 0 {
+                    errors.push(format!(
+                        "filter {} has {} bits, which is not a multiple of the {} bits of a block",
+                        idx, number_of_bits, BLOCK_BITS
+                    ));
+                }
+                (number_of_bits / 8, true)
+            }
+            // RedisBloom may allocate more bytes than the number of bits needs.
+            BloomBitmap::RedisBloom(_) => (number_of_bits.div_ceil(8), false),
+            _ => (number_of_bits.div_ceil(8), true),
+        };
+        let len = *bitmap_len as u64;
+        if len < expected_len || (exact && len != expected_len) {
+            errors.push(format!(
+                "filter {} holds a bitmap of {} bytes, while its {} bits need {} bytes",
+                idx, len, number_of_bits, expected_len
+            ));
+        }
+        if filter.num_items > filter.capacity {
+            errors.push(format!(
+                "filter {} holds {} items, more than its capacity {}",
+                idx, filter.num_items, filter.capacity
+            ));
+        }
+    }
+    errors
+}
+
+/// Print the bloom object and its filters.
+fn print_object(bf: &BloomFilterType) {
+    let expansion = match bf.expansion {
+        0 => "nonscaling".to_string(),
+        expansion => format!("expansion {}", expansion),
+    };
+    println!(
+        "  layout {}, hash {}, error rate {}, {}, {} filters, capacity {}, items {}, {} bytes",
+        bf.layout.as_str(),
+        bf.hash_function.as_str(),
+        bf.fp_rate,
+        expansion,
+        bf.filters.len(),
+        bf.capacity(),
+        bf.cardinality(),
+        bf.memory_usage()
+    );
+    for (idx, filter) in bf.filters.iter().enumerate() {
+        let bloom = &filter.bloom;
+        let number_of_bits = bloom.number_of_bits();
+        let fill_ratio = if number_of_bits == 0 {
+            0.0
+        } else {
+            bloom.number_of_set_bits() as f64 / number_of_bits as f64
+        };
+        println!(
+            "  filter {}: capacity {}, items {}, {} bits ({} bytes), {} hash functions, fill ratio {:.4}, estimated error rate {:.3e}",
+            idx,
+            filter.capacity,
+            filter.num_items,
+            number_of_bits,
+            bloom.bitmap_len(),
+            bloom.number_of_hash_functions(),
+            fill_ratio,
+            fill_ratio.powi(bloom.number_of_hash_functions() as i32)
+        );
+    }
+}
+
+/// Decode and print the value of a module data type, which is skipped unless it is a bloom object.
+/// Returns None when the value cannot be decoded, as the rest of the input cannot be located then.
+fn inspect_module_value(
+    reader: &mut DumpPayloadReader,
+    name: &str,
+    encver: i32,
+    key: &str,
+    inspection: &mut Inspection,
+) -> Option<()> {
+    if name != BLOOM_FILTER_TYPE_NAME && name != REDISBLOOM_FILTER_TYPE_NAME {
+        return reader.skip_module_values();
+    }
+    inspection.num_objects += 1;
+    println!("{} ({}, encoding version {})", key, name, encver);
+    let mut inspecting_reader = InspectingReader {
+        reader,
+        bitmap_lens: Vec::new(),
+    };
+    let loaded = if name == BLOOM_FILTER_TYPE_NAME {
+        rdb::load_bloom_object(&mut inspecting_reader, encver)
+    } else {
+        rdb::load_redisbloom_object(&mut inspecting_reader, encver)
+    };
+    let bitmap_lens = inspecting_reader.bitmap_lens;
+    let (errors, decoded) = match loaded {
+        Ok(bf) => {
+            print_object(&bf);
+            let mut errors = validate_object(&bf, &bitmap_lens);
+            let decoded = reader.load_module_eof();
+            if decoded.is_none() {
+                errors.push("the object is followed by unexpected data".to_string());
+            }
+            (errors, decoded)
+        }
+        // The values the object could not be loaded from are skipped, to inspect the rest of the input.
+        Err(err) => (vec![err.to_string()], reader.skip_module_values()),
+    };
+    if !errors.is_empty() {
+        inspection.num_invalid_objects += 1;
+    }
+    for error in errors {
+        println!("  error: {}", error);
+    }
+    decoded
+}
+
+/// Inspect a DUMP payload: the value, the RDB version which produced it and its checksum.
+fn inspect_dump(payload: &[u8], inspection: &mut Inspection) -> Result<(), String> {
+    let body_len = payload
+        .len()
+        .checked_sub(10)
+        .ok_or("the DUMP payload is too short")?;
+    let rdb_version = u16::from_le_bytes([payload[body_len], payload[body_len + 1]]);
+    let mut crc = [0u8; 8];
+    crc.copy_from_slice(&payload[body_len + 2..]);
+    println!("DUMP payload of RDB version {}", rdb_version);
+    if u64::from_le_bytes(crc) != rdb::crc64(&payload[..body_len + 2]) {
+        return Err("the checksum of the DUMP payload does not match".to_string());
+    }
+    let mut reader = DumpPayloadReader::new(&payload[..body_len]);
+    if reader.load_bytes(1) != Some(&[RDB_TYPE_MODULE_2][..]) {
+        return Err("the DUMP payload does not hold a module data type".to_string());
+    }
+    let id = reader
+        .load_length()
+        .ok_or("the DUMP payload is truncated")?;
+    let (name, encver) = rdb::module_type_name(id);
+    inspection.num_keys += 1;
+    inspect_module_value(&mut reader, &name, encver, "value", inspection)
+        .ok_or("the value of the DUMP payload cannot be decoded")?;
+    if reader.position() != body_len {
+        return Err("the DUMP payload holds unexpected data after its value".to_string());
+    }
+    Ok(())
+}
+
+/// Skip the value of a key of a data type other than the module data types.
+fn skip_value(reader: &mut DumpPayloadReader, value_type: u8) -> Option<()> {
+    let skip_strings = |reader: &mut DumpPayloadReader, count: u64| -> Option<()> {
+        for _ in 0..count {
+            reader.load_string()?;
+        }
+        Some(())
+    };
+    match value_type {
+        // String, and the encodings held in a single string: ziplists, listpacks, intsets and zipmaps.
+        0 | 9..=13 | 16 | 17 | 20 => skip_strings(reader, 1),
+        // List, set and quicklist.
+        1 | 2 | 14 => {
+            let len = reader.load_length()?;
+            skip_strings(reader, len)
+        }
+        // Sorted set, with scores saved as strings.
+        3 => {
+            for _ in 0..reader.load_length()? {
+                reader.load_string()?;
+                let score_len = reader.load_bytes(1)?[0];
+                if score_len < 253 {
+                    reader.load_bytes(score_len as usize)?;
+                }
+            }
+            Some(())
+        }
+        // Hash.
+        4 => {
+            let len = reader.load_length()?;
+            skip_strings(reader, len.checked_mul(2)?)
+        }
+        // Sorted set, with binary scores.
+        5 => {
+            for _ in 0..reader.load_length()? {
+                reader.load_string()?;
+                reader.load_bytes(8)?;
+            }
+            Some(())
+        }
+        // Quicklist of listpacks.
+        18 => {
+            for _ in 0..reader.load_length()? {
+                reader.load_length()?;
+                reader.load_string()?;
+            }
+            Some(())
+        }
+        // Stream, in its 3 versions.
+        15 | 19 | 21 => skip_stream(reader, value_type),
+        _ => None,
+    }
+}
+
+/// Skip the value of a stream: its listpacks, metadata and consumer groups.
+fn skip_stream(reader: &mut DumpPayloadReader, value_type: u8) -> Option<()> {
+    let num_listpacks = reader.load_length()?;
+    for _ in 0..num_listpacks.checked_mul(2)? {
+        reader.load_string()?;
+    }
+    // Length and last id, followed from version 2 by the first id, max deleted id and entries added.
+    let num_lengths = if value_type >= 19 { 8 } else { 3 };
+    for _ in 0..num_lengths {
+        reader.load_length()?;
+    }
+    for _ in 0..reader.load_length()? {
+        reader.load_string()?;
+        // Last id, followed from version 2 by the entries read.
+        for _ in 0..if value_type >= 19 { 3 } else { 2 } {
+            reader.load_length()?;
+        }
+        // Pending entries: id, delivery time and delivery count.
+        for _ in 0..reader.load_length()? {
+            reader.load_bytes(16 + 8)?;
+            reader.load_length()?;
+        }
+        for _ in 0..reader.load_length()? {
+            reader.load_string()?;
+            // Seen time, followed from version 3 by the active time.
+            reader.load_bytes(if value_type >= 21 { 16 } else { 8 })?;
+            let num_pending = reader.load_length()?;
+            reader.load_bytes(usize::try_from(num_pending.checked_mul(16)?).ok()?)?;
+        }
+    }
+    Some(())
+}
+
+/// Inspect an RDB file: every key holding a bloom object, and the auxiliary data saved by the module.
+fn inspect_rdb(
+    rdb_file: &[u8],
+    header_len: usize,
+    inspection: &mut Inspection,
+) -> Result<(), String> {
+    let mut reader = DumpPayloadReader::new(rdb_file);
+    reader.load_bytes(header_len);
+    let truncated = || "the RDB file is truncated or corrupted".to_string();
+    let mut expire = None;
+    loop {
+        let offset = reader.position();
+        let opcode = reader.load_bytes(1).ok_or_else(truncated)?[0];
+        match opcode {
+            RDB_OPCODE_EOF => break,
+            RDB_OPCODE_SELECTDB => {
+                let db = reader.load_length().ok_or_else(truncated)?;
+                println!("db {}", db);
+            }
+            RDB_OPCODE_RESIZEDB => {
+                reader
+                    .load_length()
+                    .and(reader.load_length())
+                    .ok_or_else(truncated)?;
+            }
+            RDB_OPCODE_SLOT_INFO => {
+                for _ in 0..3 {
+                    reader.load_length().ok_or_else(truncated)?;
+                }
+            }
+            RDB_OPCODE_AUX => {
+                reader
+                    .load_string()
+                    .and(reader.load_string())
+                    .ok_or_else(truncated)?;
+            }
+            RDB_OPCODE_FUNCTION2 => {
+                reader.load_string().ok_or_else(truncated)?;
+            }
+            RDB_OPCODE_EXPIRETIME_MS => {
+                let ms = reader.load_bytes(8).ok_or_else(truncated)?;
+                expire = Some(u64::from_le_bytes(ms.try_into().map_err(|_| truncated())?));
+            }
+            RDB_OPCODE_EXPIRETIME => {
+                let secs = reader.load_bytes(4).ok_or_else(truncated)?;
+                expire = Some(
+                    u32::from_le_bytes(secs.try_into().map_err(|_| truncated())?) as u64 * 1000,
+                );
+            }
+            RDB_OPCODE_IDLE => {
+                reader.load_length().ok_or_else(truncated)?;
+            }
+            RDB_OPCODE_FREQ => {
+                reader.load_bytes(1).ok_or_else(truncated)?;
+            }
+            RDB_OPCODE_MODULE_AUX => {
+                let id = reader.load_length().ok_or_else(truncated)?;
+                let (name, _) = rdb::module_type_name(id);
+                // The aux data is preceded by when it was saved, as an unsigned value.
+                reader.load_unsigned().ok_or_else(truncated)?;
+                if name == BLOOM_FILTER_TYPE_NAME {
+                    inspect_bloom_aux(&mut reader).ok_or_else(truncated)?;
+                } else {
+                    reader.skip_module_values().ok_or_else(truncated)?;
+                }
+            }
+            value_type => {
+                let key = reader.load_string().ok_or_else(truncated)?;
+                let key = String::from_utf8_lossy(&key);
+                inspection.num_keys += 1;
+                if value_type == RDB_TYPE_MODULE_2 {
+                    let id = reader.load_length().ok_or_else(truncated)?;
+                    let (name, encver) = rdb::module_type_name(id);
+                    let key = match expire.take() {
+                        Some(expire) => format!("key {} expiring at {} ms", key, expire),
+                        None => format!("key {}", key),
+                    };
+                    inspect_module_value(&mut reader, &name, encver, &key, inspection)
+                        .ok_or_else(|| format!("the {} cannot be decoded", key))?;
+                } else {
+                    expire = None;
+                    skip_value(&mut reader, value_type).ok_or_else(|| {
+                        format!(
+                            "key {} at offset {} has the value type {} which cannot be skipped",
+                            key, offset, value_type
+                        )
+                    })?;
+                }
+            }
+        }
+    }
+    // A checksum of zero means the server saved the file without computing it.
+    let checksum_offset = reader.position();
+    let crc = reader.load_bytes(8).ok_or_else(truncated)?;
+    let crc = u64::from_le_bytes(crc.try_into().map_err(|_| truncated())?);
+    if crc != 0 && crc != rdb::crc64(&rdb_file[..checksum_offset]) {
+        return Err("the checksum of the RDB file does not match".to_string());
+    }
+    Ok(())
+}
+
+/// Print the auxiliary data saved by the module before the keyspace.
+fn inspect_bloom_aux(reader: &mut DumpPayloadReader) -> Option<()> {
+    let module_version = reader.load_unsigned()?;
+    let encoding_version = reader.load_unsigned()?;
+    let capacity = reader.load_signed()?;
+    let expansion = reader.load_signed()?;
+    let hash_function = reader.load_unsigned()?;
+    let max_filters = reader.load_signed()?;
+    reader.load_module_eof()?;
+    let hash_function = BloomHashFunction::from_encoding(hash_function)
+        .map_or("unknown", |hash_function| hash_function.as_str());
+    println!(
+        "module aux data: module version {}, {} encoding version {}, bloom-max-item-size {}, bloom-expansion-rate {}, bloom-hash-function {}, bloom-max-filters-per-object {}",
+        module_version, BLOOM_FILTER_TYPE_NAME, encoding_version, capacity, expansion, hash_function, max_filters
+    );
+    if encoding_version > BLOOM_FILTER_TYPE_ENCODING_VERSION as u64 {
+        println!(
+            "  error: the encoding version is higher than the supported version {}",
+            BLOOM_FILTER_TYPE_ENCODING_VERSION
+        );
+    }
+    Some(())
+}
+
+/// Returns the length of the header of an RDB file, which starts with a magic string and the RDB version.
+fn rdb_header_len(input: &[u8]) -> Option<usize> {
+    let magic_len = if input.starts_with(b"REDIS") {
+        5
+    } else if input.starts_with(b"VALKEY") {
+        6
+    } else {
+        return None;
+    };
+    input
+        .get(magic_len..RDB_HEADER_LEN)?
+        .iter()
+        .all(u8::is_ascii_digit)
+        .then_some(RDB_HEADER_LEN)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut input = Vec::new();
+    let read = match args.as_slice() {
+        [] => io::stdin().lock().read_to_end(&mut input).map(|_| ()),
+        [arg] if arg == "--help" || arg == "-h" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        [path] => std::fs::read(path).map(|content| input = content),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(err) = read {
+        eprintln!("valkey-bloom-inspect: failed to read the input: {}", err);
+        return ExitCode::FAILURE;
+    }
+    let mut inspection = Inspection::default();
+    let inspected = match rdb_header_len(&input) {
+        Some(header_len) => {
+            println!("RDB file {}", String::from_utf8_lossy(&input[..header_len]));
+            inspect_rdb(&input, header_len, &mut inspection)
+        }
+        None => inspect_dump(&input, &mut inspection),
+    };
+    println!(
+        "{} keys, {} bloom objects, {} of which cannot be loaded or fail validation",
+        inspection.num_keys, inspection.num_objects, inspection.num_invalid_objects
+    );
+    if let Err(err) = inspected {
+        println!("error: {}", err);
+        return ExitCode::FAILURE;
+    }
+    if inspection.num_invalid_objects > 0 {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
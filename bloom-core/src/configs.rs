@@ -0,0 +1,60 @@
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Configurations
+pub const BLOOM_CAPACITY_DEFAULT: i64 = 100000;
+pub const BLOOM_CAPACITY_MIN: u64 = 1;
+pub const BLOOM_CAPACITY_MAX: u64 = i64::MAX as u64;
+
+pub const BLOOM_EXPANSION_DEFAULT: i64 = 2;
+pub const BLOOM_EXPANSION_MIN: u32 = 1;
+pub const BLOOM_EXPANSION_MAX: u32 = 10;
+
+pub const BLOOM_FP_RATE_DEFAULT: f32 = 0.001;
+pub const BLOOM_FP_RATE_MIN: f32 = 0.0;
+pub const BLOOM_FP_RATE_MAX: f32 = 1.0;
+
+pub const BLOOM_MAX_FILTERS_PER_OBJ_DEFAULT: i64 = i32::MAX as i64;
+pub const BLOOM_MAX_FILTERS_PER_OBJ_MIN: i32 = 1;
+pub const BLOOM_MAX_FILTERS_PER_OBJ_MAX: i32 = i32::MAX;
+
+pub const BLOOM_MEMORY_LIMIT_PER_OBJ_DEFAULT: i64 = 128 * 1024 * 1024;
+pub const BLOOM_MEMORY_LIMIT_PER_OBJ_MIN: i64 = 0;
+pub const BLOOM_MEMORY_LIMIT_PER_OBJ_MAX: i64 = i64::MAX;
+
+lazy_static! {
+    pub static ref BLOOM_MAX_FILTERS_PER_OBJ: AtomicI64 =
+        AtomicI64::new(BLOOM_MAX_FILTERS_PER_OBJ_DEFAULT);
+    pub static ref BLOOM_MEMORY_LIMIT_PER_OBJ: AtomicI64 =
+        AtomicI64::new(BLOOM_MEMORY_LIMIT_PER_OBJ_DEFAULT);
+}
+
+/// Returns the configured maximum number of sub filters that a bloom object can scale out to.
+pub fn max_filters_per_object() -> i32 {
+    BLOOM_MAX_FILTERS_PER_OBJ.load(Ordering::Relaxed) as i32
+}
+
+/// Returns the configured maximum number of bytes that a bloom object can be created with or scale out to.
+pub fn memory_limit_per_object() -> u64 {
+    BLOOM_MEMORY_LIMIT_PER_OBJ.load(Ordering::Relaxed) as u64
+}
+
+/// Constants
+pub const TIGHTENING_RATIO: f32 = 0.5;
+/// Number of allocated bytes which count as one unit of the free effort of a bloom object.
+pub const FREE_EFFORT_BYTES_PER_UNIT: usize = 64 * 1024;
+/// Bloom objects with a higher free effort are not defragmented, matching the lazyfree threshold of the server.
+/// Copying them would block the main thread, and their bitmaps are large allocations which do not fragment.
+pub const DEFRAG_MAX_FREE_EFFORT: usize = 64;
+/// Maximum number of bytes of a bitmap chunk replied by BF.SCANDUMP, the same as RedisBloom.
+pub const SCANDUMP_MAX_CHUNK_SIZE: usize = 10 * 1024 * 1024;
+/// Below constants are fixed seed and sip keys to help create bloom objects using the same seed and to restore the bloom objects with the same hasher which
+/// generated using rust crate bloomfilter https://crates.io/crates/bloomfilter
+pub const FIXED_SEED: [u8; 32] = [
+    89, 15, 245, 34, 234, 120, 17, 218, 167, 20, 216, 9, 59, 62, 123, 217, 29, 137, 138, 115, 62,
+    152, 136, 135, 48, 127, 151, 205, 40, 7, 51, 131,
+];
+pub const FIXED_SIP_KEY_ONE_A: u64 = 15713473521876537177;
+pub const FIXED_SIP_KEY_ONE_B: u64 = 15671187751654921383;
+pub const FIXED_SIP_KEY_TWO_A: u64 = 9766223185946773789;
+pub const FIXED_SIP_KEY_TWO_B: u64 = 9453907914610147120;
@@ -0,0 +1,10 @@
+//! Bloom filters of the valkey-bloom module, usable outside of the server.
+//!
+//! Bloom objects built with [`utils::BloomFilterType`] hash items the same way as the module, so batch jobs
+//! can pre-build filters and clients can check membership locally. [`rdb::dump_payload`] serializes an object
+//! into a payload which RESTORE loads as a bloom key, and [`rdb::restore_payload`] loads the payload replied
+//! by DUMP for a bloom key of the module or of RedisBloom.
+pub mod configs;
+pub mod metrics;
+pub mod rdb;
+pub mod utils;
@@ -0,0 +1,31 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Module wide metrics of the bloom objects.
+/// They are atomics as bloom objects can be freed in a background thread by lazyfree.
+pub static BLOOM_NUM_OBJECTS: AtomicU64 = AtomicU64::new(0);
+pub static BLOOM_OBJECT_TOTAL_MEMORY_BYTES: AtomicU64 = AtomicU64::new(0);
+pub static BLOOM_NUM_FILTERS_ACROSS_OBJECTS: AtomicU64 = AtomicU64::new(0);
+
+/// Account for the creation of a bloom object, excluding its filters.
+pub fn object_created(num_bytes: usize) {
+    BLOOM_NUM_OBJECTS.fetch_add(1, Ordering::Relaxed);
+    BLOOM_OBJECT_TOTAL_MEMORY_BYTES.fetch_add(num_bytes as u64, Ordering::Relaxed);
+}
+
+/// Account for the drop of a bloom object, excluding its filters.
+pub fn object_dropped(num_bytes: usize) {
+    BLOOM_NUM_OBJECTS.fetch_sub(1, Ordering::Relaxed);
+    BLOOM_OBJECT_TOTAL_MEMORY_BYTES.fetch_sub(num_bytes as u64, Ordering::Relaxed);
+}
+
+/// Account for the creation of a filter of a bloom object.
+pub fn filter_created(num_bytes: usize) {
+    BLOOM_NUM_FILTERS_ACROSS_OBJECTS.fetch_add(1, Ordering::Relaxed);
+    BLOOM_OBJECT_TOTAL_MEMORY_BYTES.fetch_add(num_bytes as u64, Ordering::Relaxed);
+}
+
+/// Account for the drop of a filter of a bloom object.
+pub fn filter_dropped(num_bytes: usize) {
+    BLOOM_NUM_FILTERS_ACROSS_OBJECTS.fetch_sub(1, Ordering::Relaxed);
+    BLOOM_OBJECT_TOTAL_MEMORY_BYTES.fetch_sub(num_bytes as u64, Ordering::Relaxed);
+}
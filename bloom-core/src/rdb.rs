@@ -0,0 +1,737 @@
+use crate::configs::{BLOOM_MAX_FILTERS_PER_OBJ_MAX, TIGHTENING_RATIO};
+use crate::utils::{
+    BloomFilter, BloomFilterLayout, BloomFilterType, BloomHashFunction, CompatBloom, BLOCK_BITS,
+    REDISBLOOM_OPT_FORCE64, REDISBLOOM_OPT_NOROUND, REDISBLOOM_OPT_NO_SCALING,
+};
+use crc::{Crc, CRC_64_REDIS};
+use std::fmt;
+
+/// Name of the data type of the bloom objects of this module.
+pub const BLOOM_FILTER_TYPE_NAME: &str = "bloomfltr";
+
+/// Encoding version 1 adds the layout of the filters of the object.
+/// Encoding version 2 adds the hash function of the object.
+/// Encoding version 3 allows the capacity and number of items of a filter to exceed u32::MAX.
+/// Encoding version 4 adds the redisbloom layout and the MurmurHash64A and MurmurHash2 hash functions.
+pub const BLOOM_FILTER_TYPE_ENCODING_VERSION: i32 = 4;
+
+/// Name of the data type of the RedisBloom module.
+pub const REDISBLOOM_FILTER_TYPE_NAME: &str = "MBbloom--";
+
+/// Encoding version of the data type of the RedisBloom module, which can be loaded up to this version.
+/// Version 1 adds the number of bits of the filters, version 2 the options and version 4 the growth.
+pub const REDISBLOOM_FILTER_TYPE_ENCODING_VERSION: i32 = 4;
+
+/// Source of the values of a bloom object, in the order they were saved to an RDB file or DUMP payload.
+pub trait RdbReader {
+    type Buffer: AsRef<[u8]>;
+
+    fn load_unsigned(&mut self) -> Option<u64>;
+    fn load_float(&mut self) -> Option<f32>;
+    fn load_double(&mut self) -> Option<f64>;
+    fn load_string_buffer(&mut self) -> Option<Self::Buffer>;
+}
+
+/// Destination of the values of a bloom object saved to an RDB file or DUMP payload.
+pub trait RdbWriter {
+    fn save_unsigned(&mut self, value: u64);
+    fn save_float(&mut self, value: f32);
+    fn save_double(&mut self, value: f64);
+    fn save_string_buffer(&mut self, value: &[u8]);
+}
+
+/// Reasons a bloom object cannot be loaded from an RDB file or DUMP payload.
+#[derive(Debug, PartialEq)]
+pub enum RdbLoadError {
+    Truncated,
+    UnsupportedEncodingVersion {
+        type_name: &'static str,
+        encver: i32,
+        supported: i32,
+    },
+    InvalidNumberOfFilters {
+        type_name: &'static str,
+        num_filters: u64,
+        max_filters: i32,
+    },
+    InvalidLayout(u64),
+    InvalidHashFunction(u64),
+    InvalidBitmap {
+        type_name: &'static str,
+        bitmap_len: usize,
+        number_of_bits: u64,
+    },
+    InvalidPayload(&'static str),
+    ChecksumMismatch,
+    UnknownDataType(String),
+}
+
+impl fmt::Display for RdbLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RdbLoadError::Truncated => {
+                write!(f, "Cannot load bloom object because its data is truncated")
+            }
+            RdbLoadError::UnsupportedEncodingVersion {
+                type_name,
+                encver,
+                supported,
+            } => write!(
+                f,
+                "Cannot load {} data type of version {} because it is higher than the loaded module's {} supported version {}",
+                type_name, encver, type_name, supported
+            ),
+            RdbLoadError::InvalidNumberOfFilters {
+                type_name,
+                num_filters,
+                max_filters,
+            } => write!(
+                f,
+                "Cannot load {} data type with {} filters because it is not between 1 and the max number of filters per object {}",
+                type_name, num_filters, max_filters
+            ),
+            RdbLoadError::InvalidLayout(layout) => write!(
+                f,
+                "Cannot load bloom object with the unknown layout encoding {}",
+                layout
+            ),
+            RdbLoadError::InvalidHashFunction(hash_function) => write!(
+                f,
+                "Cannot load bloom object with the unknown hash function encoding {}",
+                hash_function
+            ),
+            RdbLoadError::InvalidBitmap {
+                type_name,
+                bitmap_len,
+                number_of_bits,
+            } => write!(
+                f,
+                "Cannot load {} data type with a bitmap of {} bytes for {} bits",
+                type_name, bitmap_len, number_of_bits
+            ),
+            RdbLoadError::InvalidPayload(reason) => {
+                write!(f, "Cannot load DUMP payload because {}", reason)
+            }
+            RdbLoadError::ChecksumMismatch => write!(
+                f,
+                "Cannot load DUMP payload because its checksum does not match"
+            ),
+            RdbLoadError::UnknownDataType(name) => write!(
+                f,
+                "Cannot load DUMP payload of the unknown data type {}",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RdbLoadError {}
+
+/// Save a bloom object in the format of the bloomfltr data type.
+pub fn save_bloom_object<W: RdbWriter>(rdb: &mut W, item: &BloomFilterType) {
+    rdb.save_unsigned(item.filters.len() as u64);
+    rdb.save_unsigned(item.expansion as u64);
+    rdb.save_float(item.fp_rate);
+    rdb.save_unsigned(item.layout.encoding());
+    rdb.save_unsigned(item.hash_function.encoding());
+    let mut filter_list_iter = item.filters.iter().peekable();
+    while let Some(filter) = filter_list_iter.next() {
+        let bloom = &filter.bloom;
+        rdb.save_string_buffer(&bloom.bitmap());
+        rdb.save_unsigned(bloom.number_of_bits());
+        rdb.save_unsigned(bloom.number_of_hash_functions() as u64);
+        rdb.save_unsigned(filter.capacity);
+        if filter_list_iter.peek().is_none() {
+            rdb.save_unsigned(filter.num_items);
+        }
+    }
+}
+
+/// Load a bloom object saved in the format of the bloomfltr data type with the encoding version.
+pub fn load_bloom_object<R: RdbReader>(
+    rdb: &mut R,
+    encver: i32,
+) -> Result<BloomFilterType, RdbLoadError> {
+    let mut filters = Vec::new();
+    if encver > BLOOM_FILTER_TYPE_ENCODING_VERSION {
+        return Err(RdbLoadError::UnsupportedEncodingVersion {
+            type_name: BLOOM_FILTER_TYPE_NAME,
+            encver,
+            supported: BLOOM_FILTER_TYPE_ENCODING_VERSION,
+        });
+    }
+    // Objects are loaded with all their filters even when the configured max number of filters per object was
+    // lowered below it, as the config only limits scaling out.
+    let num_filters = rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?;
+    if num_filters == 0 || num_filters > BLOOM_MAX_FILTERS_PER_OBJ_MAX as u64 {
+        return Err(RdbLoadError::InvalidNumberOfFilters {
+            type_name: BLOOM_FILTER_TYPE_NAME,
+            num_filters,
+            max_filters: BLOOM_MAX_FILTERS_PER_OBJ_MAX,
+        });
+    }
+    let expansion = rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?;
+    let fp_rate = rdb.load_float().ok_or(RdbLoadError::Truncated)?;
+    // Objects saved before encoding version 1 only support the standard layout.
+    let layout = if encver >= 1 {
+        let layout = rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?;
+        BloomFilterLayout::from_encoding(layout).ok_or(RdbLoadError::InvalidLayout(layout))?
+    } else {
+        BloomFilterLayout::Standard
+    };
+    // Objects saved before encoding version 2 are hashed with SipHash using the fixed sip keys.
+    let hash_function = if encver >= 2 {
+        let hash_function = rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?;
+        BloomHashFunction::from_encoding(hash_function)
+            .ok_or(RdbLoadError::InvalidHashFunction(hash_function))?
+    } else {
+        BloomHashFunction::SipHash
+    };
+    for i in 0..num_filters {
+        let (Some(bitmap), Some(number_of_bits), Some(number_of_hash_functions), Some(capacity)) = (
+            rdb.load_string_buffer(),
+            rdb.load_unsigned(),
+            rdb.load_unsigned(),
+            rdb.load_unsigned(),
+        ) else {
+            return Err(RdbLoadError::Truncated);
+        };
+        // Only load num_items when it's the last filter
+        let num_items = if i == num_filters - 1 {
+            rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?
+        } else {
+            capacity
+        };
+        // The bitmap must hold exactly the number of bits, in whole blocks for the blocked layout, as the bit
+        // positions of items are computed from the number of bits. RedisBloom may allocate more bytes.
+        let bitmap_len = bitmap.as_ref().len();
+        let expected_len = number_of_bits.div_ceil(8);
+        if number_of_bits == 0
+            || (bitmap_len as u64) < expected_len
+            || (layout != BloomFilterLayout::RedisBloom && bitmap_len as u64 != expected_len)
+            || (layout == BloomFilterLayout::Blocked && number_of_bits % BLOCK_BITS != 0)
+        {
+            return Err(RdbLoadError::InvalidBitmap {
+                type_name: BLOOM_FILTER_TYPE_NAME,
+                bitmap_len,
+                number_of_bits,
+            });
+        }
+        // Objects saved before encoding version 3 hold u32 capacities and number of items, which are
+        // encoded as unsigned 64 bit values like in later versions, so they are widened as is.
+        let filter = BloomFilter::from_existing(
+            layout,
+            hash_function,
+            bitmap.as_ref(),
+            number_of_bits,
+            number_of_hash_functions as u32,
+            num_items,
+            capacity,
+        );
+        filters.push(filter);
+    }
+    Ok(BloomFilterType::from_existing(
+        expansion as u32,
+        fp_rate,
+        layout,
+        hash_function,
+        filters,
+    ))
+}
+
+/// Save a bloom object of the redisbloom layout in the format of the MBbloom-- data type.
+pub fn save_redisbloom_object<W: RdbWriter>(rdb: &mut W, item: &BloomFilterType) {
+    rdb.save_unsigned(item.cardinality() as u64);
+    rdb.save_unsigned(item.filters.len() as u64);
+    let mut options = REDISBLOOM_OPT_NOROUND;
+    if item.hash_function == BloomHashFunction::MurmurHash64A {
+        options |= REDISBLOOM_OPT_FORCE64;
+    }
+    if item.expansion == 0 {
+        options |= REDISBLOOM_OPT_NO_SCALING;
+    }
+    rdb.save_unsigned(options);
+    rdb.save_unsigned(item.expansion.max(1) as u64);
+    let mut error = item.fp_rate as f64;
+    for filter in &item.filters {
+        let bloom = &filter.bloom;
+        rdb.save_unsigned(filter.capacity);
+        rdb.save_double(error);
+        rdb.save_unsigned(bloom.number_of_hash_functions() as u64);
+        rdb.save_double(CompatBloom::bits_per_item(error));
+        rdb.save_unsigned(bloom.number_of_bits());
+        // The number of bits is never rounded up to a power of two.
+        rdb.save_unsigned(0);
+        rdb.save_string_buffer(&bloom.bitmap());
+        rdb.save_unsigned(filter.num_items);
+        error *= TIGHTENING_RATIO as f64;
+    }
+}
+
+/// Load a bloom object saved in the format of the MBbloom-- data type with the encoding version.
+/// Items saved with the FORCE64 option are hashed with MurmurHash64A, and older ones with MurmurHash2.
+pub fn load_redisbloom_object<R: RdbReader>(
+    rdb: &mut R,
+    encver: i32,
+) -> Result<BloomFilterType, RdbLoadError> {
+    if encver > REDISBLOOM_FILTER_TYPE_ENCODING_VERSION {
+        return Err(RdbLoadError::UnsupportedEncodingVersion {
+            type_name: REDISBLOOM_FILTER_TYPE_NAME,
+            encver,
+            supported: REDISBLOOM_FILTER_TYPE_ENCODING_VERSION,
+        });
+    }
+    let _num_items = rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?;
+    let num_filters = rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?;
+    if num_filters == 0 || num_filters > BLOOM_MAX_FILTERS_PER_OBJ_MAX as u64 {
+        return Err(RdbLoadError::InvalidNumberOfFilters {
+            type_name: REDISBLOOM_FILTER_TYPE_NAME,
+            num_filters,
+            max_filters: BLOOM_MAX_FILTERS_PER_OBJ_MAX,
+        });
+    }
+    let options = if encver >= 2 {
+        rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?
+    } else {
+        0
+    };
+    // Objects saved before encoding version 4 scale out with a growth of 2.
+    let growth = if encver >= 4 {
+        rdb.load_unsigned().ok_or(RdbLoadError::Truncated)?
+    } else {
+        2
+    };
+    let expansion = if options & REDISBLOOM_OPT_NO_SCALING != 0 {
+        0
+    } else {
+        growth as u32
+    };
+    let hash_function = if options & REDISBLOOM_OPT_FORCE64 != 0 {
+        BloomHashFunction::MurmurHash64A
+    } else {
+        BloomHashFunction::MurmurHash2
+    };
+    let mut fp_rate = None;
+    let mut filters = Vec::new();
+    for _ in 0..num_filters {
+        let (Some(capacity), Some(error), Some(number_of_hash_functions), Some(bits_per_item)) = (
+            rdb.load_unsigned(),
+            rdb.load_double(),
+            rdb.load_unsigned(),
+            rdb.load_double(),
+        ) else {
+            return Err(RdbLoadError::Truncated);
+        };
+        // Objects saved before encoding version 1 derive the number of bits from the bits per item.
+        let number_of_bits = if encver >= 1 {
+            let (Some(number_of_bits), Some(_n2)) = (rdb.load_unsigned(), rdb.load_unsigned())
+            else {
+                return Err(RdbLoadError::Truncated);
+            };
+            number_of_bits
+        } else {
+            (capacity as f64 * bits_per_item) as u64
+        };
+        let (Some(bitmap), Some(num_items)) = (rdb.load_string_buffer(), rdb.load_unsigned())
+        else {
+            return Err(RdbLoadError::Truncated);
+        };
+        let bitmap_len = bitmap.as_ref().len();
+        if number_of_bits == 0 || (bitmap_len as u64) < number_of_bits.div_ceil(8) {
+            return Err(RdbLoadError::InvalidBitmap {
+                type_name: REDISBLOOM_FILTER_TYPE_NAME,
+                bitmap_len,
+                number_of_bits,
+            });
+        }
+        // The first filter holds the false positive rate of the object, which is tightened for later filters.
+        fp_rate.get_or_insert(error as f32);
+        let filter = BloomFilter::from_existing(
+            BloomFilterLayout::RedisBloom,
+            hash_function,
+            bitmap.as_ref(),
+            number_of_bits,
+            number_of_hash_functions as u32,
+            num_items,
+            capacity,
+        );
+        filters.push(filter);
+    }
+    Ok(BloomFilterType::from_existing(
+        expansion,
+        fp_rate.ok_or(RdbLoadError::Truncated)?,
+        BloomFilterLayout::RedisBloom,
+        hash_function,
+        filters,
+    ))
+}
+
+/// RDB type of the values of module data types which are saved with opcodes, used by DUMP payloads.
+pub const RDB_TYPE_MODULE_2: u8 = 7;
+/// RDB version written in the footer of DUMP payloads. It is the version of Valkey 8.0, which is accepted by
+/// RESTORE in Valkey 8.0 and later.
+pub const DUMP_RDB_VERSION: u16 = 11;
+
+const RDB_MODULE_OPCODE_EOF: u64 = 0;
+const RDB_MODULE_OPCODE_SINT: u64 = 1;
+const RDB_MODULE_OPCODE_UINT: u64 = 2;
+const RDB_MODULE_OPCODE_FLOAT: u64 = 3;
+const RDB_MODULE_OPCODE_DOUBLE: u64 = 4;
+const RDB_MODULE_OPCODE_STRING: u64 = 5;
+
+const RDB_ENC_INT8: u8 = 0;
+const RDB_ENC_INT16: u8 = 1;
+const RDB_ENC_INT32: u8 = 2;
+const RDB_ENC_LZF: u8 = 3;
+
+/// Characters of the names of module data types, each encoded in 6 bits of the module id.
+const MODULE_TYPE_NAME_CHARSET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// CRC64 of the footer of DUMP payloads and RDB files, the Jones polynomial used by the server.
+const DUMP_CRC64: Crc<u64> = Crc::<u64>::new(&CRC_64_REDIS);
+
+/// Returns the checksum which the server computes for DUMP payloads and RDB files.
+pub fn crc64(bytes: &[u8]) -> u64 {
+    DUMP_CRC64.checksum(bytes)
+}
+
+/// Returns the 64 bit id of a module data type, its 9 character name followed by 10 bits of encoding version.
+pub fn module_type_id(name: &str, encver: i32) -> u64 {
+    let name_id = name.bytes().fold(0u64, |id, c| {
+        let position = MODULE_TYPE_NAME_CHARSET
+            .iter()
+            .position(|&charset_c| charset_c == c)
+            .unwrap_or(0);
+        (id << 6) | position as u64
+    });
+    (name_id << 10) | (encver as u64 & 1023)
+}
+
+/// Returns the name and encoding version of a module data type from its 64 bit id.
+pub fn module_type_name(id: u64) -> (String, i32) {
+    let mut name_id = id >> 10;
+    let mut name = vec![0u8; 9];
+    for c in name.iter_mut().rev() {
+        *c = MODULE_TYPE_NAME_CHARSET[(name_id & 63) as usize];
+        name_id >>= 6;
+    }
+    (
+        String::from_utf8_lossy(&name).into_owned(),
+        (id & 1023) as i32,
+    )
+}
+
+/// Writes the values of a module data type the way the server serializes them for DUMP and RDB files.
+#[derive(Default)]
+pub struct DumpPayloadWriter {
+    buf: Vec<u8>,
+}
+
+impl DumpPayloadWriter {
+    pub fn new() -> DumpPayloadWriter {
+        DumpPayloadWriter::default()
+    }
+
+    /// Append a length or unsigned value in the variable size encoding of RDB lengths.
+    pub fn save_length(&mut self, len: u64) {
+        if len < 1 << 6 {
+            self.buf.push(len as u8);
+        } else if len < 1 << 14 {
+            self.buf.push(0x40 | (len >> 8) as u8);
+            self.buf.push(len as u8);
+        } else if len <= u32::MAX as u64 {
+            self.buf.push(0x80);
+            self.buf.extend_from_slice(&(len as u32).to_be_bytes());
+        } else {
+            self.buf.push(0x81);
+            self.buf.extend_from_slice(&len.to_be_bytes());
+        }
+    }
+
+    /// Append a string as its length followed by its bytes, without compression.
+    pub fn save_raw_string(&mut self, value: &[u8]) {
+        self.save_length(value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+
+    /// Append bytes as they are.
+    pub fn save_bytes(&mut self, value: &[u8]) {
+        self.buf.extend_from_slice(value);
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl RdbWriter for DumpPayloadWriter {
+    fn save_unsigned(&mut self, value: u64) {
+        self.save_length(RDB_MODULE_OPCODE_UINT);
+        self.save_length(value);
+    }
+
+    fn save_float(&mut self, value: f32) {
+        self.save_length(RDB_MODULE_OPCODE_FLOAT);
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn save_double(&mut self, value: f64) {
+        self.save_length(RDB_MODULE_OPCODE_DOUBLE);
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn save_string_buffer(&mut self, value: &[u8]) {
+        self.save_length(RDB_MODULE_OPCODE_STRING);
+        self.save_raw_string(value);
+    }
+}
+
+/// Reads the values of a module data type the way the server serializes them for DUMP and RDB files.
+/// Strings may be integer encoded or LZF compressed, as the server does when saving them.
+pub struct DumpPayloadReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> DumpPayloadReader<'a> {
+    pub fn new(buf: &'a [u8]) -> DumpPayloadReader<'a> {
+        DumpPayloadReader { buf, pos: 0 }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Read the next bytes as they are.
+    pub fn load_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.buf.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    /// Read a length, returning whether it is the special encoding of a string rather than a length.
+    fn load_length_or_encoding(&mut self) -> Option<(u64, bool)> {
+        let first = self.load_bytes(1)?[0];
+        match first >> 6 {
+            0 => Some(((first & 0x3f) as u64, false)),
+            1 => {
+                let second = self.load_bytes(1)?[0];
+                Some(((((first & 0x3f) as u64) << 8) | second as u64, false))
+            }
+            3 => Some(((first & 0x3f) as u64, true)),
+            _ => match first {
+                0x80 => {
+                    let bytes = self.load_bytes(4)?;
+                    Some((u32::from_be_bytes(bytes.try_into().ok()?) as u64, false))
+                }
+                0x81 => {
+                    let bytes = self.load_bytes(8)?;
+                    Some((u64::from_be_bytes(bytes.try_into().ok()?), false))
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// Read a length or unsigned value in the variable size encoding of RDB lengths.
+    pub fn load_length(&mut self) -> Option<u64> {
+        match self.load_length_or_encoding()? {
+            (len, false) => Some(len),
+            (_, true) => None,
+        }
+    }
+
+    /// Read a string, which may be integer encoded or LZF compressed.
+    pub fn load_string(&mut self) -> Option<Vec<u8>> {
+        let (len, encoded) = self.load_length_or_encoding()?;
+        if !encoded {
+            return self
+                .load_bytes(usize::try_from(len).ok()?)
+                .map(<[u8]>::to_vec);
+        }
+        match len as u8 {
+            RDB_ENC_INT8 => {
+                let bytes = self.load_bytes(1)?;
+                Some((bytes[0] as i8).to_string().into_bytes())
+            }
+            RDB_ENC_INT16 => {
+                let bytes = self.load_bytes(2)?;
+                Some(
+                    i16::from_le_bytes(bytes.try_into().ok()?)
+                        .to_string()
+                        .into_bytes(),
+                )
+            }
+            RDB_ENC_INT32 => {
+                let bytes = self.load_bytes(4)?;
+                Some(
+                    i32::from_le_bytes(bytes.try_into().ok()?)
+                        .to_string()
+                        .into_bytes(),
+                )
+            }
+            RDB_ENC_LZF => {
+                let compressed_len = usize::try_from(self.load_length()?).ok()?;
+                let len = usize::try_from(self.load_length()?).ok()?;
+                lzf_decompress(self.load_bytes(compressed_len)?, len)
+            }
+            _ => None,
+        }
+    }
+
+    /// Read the opcode of the next value of a module data type, which must be the expected one.
+    fn load_opcode(&mut self, opcode: u64) -> Option<()> {
+        (self.load_length()? == opcode).then_some(())
+    }
+
+    /// Read a signed value of a module data type, as saved by RedisModule_SaveSigned.
+    pub fn load_signed(&mut self) -> Option<i64> {
+        self.load_opcode(RDB_MODULE_OPCODE_SINT)?;
+        self.load_length().map(|value| value as i64)
+    }
+
+    /// Read the end of the values of a module data type.
+    pub fn load_module_eof(&mut self) -> Option<()> {
+        self.load_opcode(RDB_MODULE_OPCODE_EOF)
+    }
+
+    /// Skip the values of a module data type up to and including their end, whatever their data type.
+    pub fn skip_module_values(&mut self) -> Option<()> {
+        loop {
+            match self.load_length()? {
+                RDB_MODULE_OPCODE_EOF => return Some(()),
+                RDB_MODULE_OPCODE_SINT | RDB_MODULE_OPCODE_UINT => {
+                    self.load_length()?;
+                }
+                RDB_MODULE_OPCODE_FLOAT => {
+                    self.load_bytes(4)?;
+                }
+                RDB_MODULE_OPCODE_DOUBLE => {
+                    self.load_bytes(8)?;
+                }
+                RDB_MODULE_OPCODE_STRING => {
+                    self.load_string()?;
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+impl RdbReader for DumpPayloadReader<'_> {
+    type Buffer = Vec<u8>;
+
+    fn load_unsigned(&mut self) -> Option<u64> {
+        self.load_opcode(RDB_MODULE_OPCODE_UINT)?;
+        self.load_length()
+    }
+
+    fn load_float(&mut self) -> Option<f32> {
+        self.load_opcode(RDB_MODULE_OPCODE_FLOAT)?;
+        Some(f32::from_le_bytes(self.load_bytes(4)?.try_into().ok()?))
+    }
+
+    fn load_double(&mut self) -> Option<f64> {
+        self.load_opcode(RDB_MODULE_OPCODE_DOUBLE)?;
+        Some(f64::from_le_bytes(self.load_bytes(8)?.try_into().ok()?))
+    }
+
+    fn load_string_buffer(&mut self) -> Option<Vec<u8>> {
+        self.load_opcode(RDB_MODULE_OPCODE_STRING)?;
+        self.load_string()
+    }
+}
+
+/// Maximum number of decompressed bytes per LZF compressed byte: the longest back reference takes 3 bytes and
+/// copies 264 bytes.
+const LZF_MAX_EXPANSION: usize = 88;
+
+/// Decompress LZF compressed data of the given decompressed length, as compressed by the server.
+/// The length is read from the data, so lengths which the compressed bytes cannot expand to are rejected
+/// before the output is allocated.
+fn lzf_decompress(input: &[u8], len: usize) -> Option<Vec<u8>> {
+    if len > input.len().saturating_mul(LZF_MAX_EXPANSION) {
+        return None;
+    }
+    let mut output = Vec::with_capacity(len);
+    let mut ip = 0;
+    while ip < input.len() {
+        let ctrl = input[ip] as usize;
+        ip += 1;
+        if ctrl < 32 {
+            // Literal run of ctrl + 1 bytes.
+            let literal = input.get(ip..ip + ctrl + 1)?;
+            output.extend_from_slice(literal);
+            ip += ctrl + 1;
+        } else {
+            // Back reference to earlier output, which may overlap the bytes being copied.
+            let mut ref_len = ctrl >> 5;
+            if ref_len == 7 {
+                ref_len += *input.get(ip)? as usize;
+                ip += 1;
+            }
+            let offset = ((ctrl & 0x1f) << 8) + *input.get(ip)? as usize + 1;
+            ip += 1;
+            let start = output.len().checked_sub(offset)?;
+            for i in 0..ref_len + 2 {
+                output.push(output[start + i]);
+            }
+        }
+        if output.len() > len {
+            return None;
+        }
+    }
+    (output.len() == len).then_some(output)
+}
+
+/// Returns the payload of the DUMP command for a bloom object, which can be restored with RESTORE.
+pub fn dump_payload(item: &BloomFilterType) -> Vec<u8> {
+    let mut writer = DumpPayloadWriter::new();
+    writer.save_bytes(&[RDB_TYPE_MODULE_2]);
+    writer.save_length(module_type_id(
+        BLOOM_FILTER_TYPE_NAME,
+        BLOOM_FILTER_TYPE_ENCODING_VERSION,
+    ));
+    save_bloom_object(&mut writer, item);
+    writer.save_length(RDB_MODULE_OPCODE_EOF);
+    writer.save_bytes(&DUMP_RDB_VERSION.to_le_bytes());
+    let mut payload = writer.into_inner();
+    let crc = crc64(&payload);
+    payload.extend_from_slice(&crc.to_le_bytes());
+    payload
+}
+
+/// Returns the bloom object of a payload of the DUMP command, of either the bloomfltr or the MBbloom-- data type.
+pub fn restore_payload(payload: &[u8]) -> Result<BloomFilterType, RdbLoadError> {
+    let Some(body_len) = payload.len().checked_sub(10) else {
+        return Err(RdbLoadError::InvalidPayload("it is too short"));
+    };
+    // The footer holds the RDB version, which any version of the server may have produced, and the checksum.
+    let (content, crc) = payload.split_at(body_len + 2);
+    let mut crc_bytes = [0u8; 8];
+    crc_bytes.copy_from_slice(crc);
+    if u64::from_le_bytes(crc_bytes) != crc64(content) {
+        return Err(RdbLoadError::ChecksumMismatch);
+    }
+    let mut reader = DumpPayloadReader::new(&payload[..body_len]);
+    if reader.load_bytes(1) != Some(&[RDB_TYPE_MODULE_2][..]) {
+        return Err(RdbLoadError::InvalidPayload(
+            "it does not hold a module data type",
+        ));
+    }
+    let id = reader.load_length().ok_or(RdbLoadError::Truncated)?;
+    let (name, encver) = module_type_name(id);
+    let item = match name.as_str() {
+        BLOOM_FILTER_TYPE_NAME => load_bloom_object(&mut reader, encver)?,
+        REDISBLOOM_FILTER_TYPE_NAME => load_redisbloom_object(&mut reader, encver)?,
+        _ => return Err(RdbLoadError::UnknownDataType(name)),
+    };
+    if reader.load_module_eof().is_none() || reader.position() != body_len {
+        return Err(RdbLoadError::InvalidPayload(
+            "it holds unexpected data after the bloom object",
+        ));
+    }
+    Ok(item)
+}
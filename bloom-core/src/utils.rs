@@ -0,0 +1,2798 @@
+use crate::configs::{
+    self, DEFRAG_MAX_FREE_EFFORT, FIXED_SIP_KEY_ONE_A, FIXED_SIP_KEY_ONE_B, FIXED_SIP_KEY_TWO_A,
+    FIXED_SIP_KEY_TWO_B, FREE_EFFORT_BYTES_PER_UNIT, SCANDUMP_MAX_CHUNK_SIZE, TIGHTENING_RATIO,
+};
+use crate::metrics;
+use bloomfilter;
+use bloomfilter::reexports::bit_vec::BitVec;
+use bloomfilter::reexports::siphasher::sip::SipHasher13;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "valkey-module")]
+use valkey_module::ValkeyError;
+
+/// KeySpace Notification Events
+pub const ADD_EVENT: &str = "bloom.add";
+pub const RESERVE_EVENT: &str = "bloom.reserve";
+pub const CLEAR_EVENT: &str = "bloom.clear";
+pub const COMPACT_EVENT: &str = "bloom.compact";
+pub const SWAP_EVENT: &str = "bloom.swap";
+pub const LOADCHUNK_EVENT: &str = "bloom.loadchunk";
+pub const MERGE_EVENT: &str = "bloom.merge";
+
+/// Errors
+pub const ERROR: &str = "ERROR";
+pub const WRONG_TYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+pub const NON_SCALING_FILTER_FULL: &str = "ERR non scaling filter is full";
+pub const NOT_FOUND: &str = "ERR not found";
+pub const ITEM_EXISTS: &str = "ERR item exists";
+pub const INVALID_INFO_VALUE: &str = "ERR invalid information value";
+pub const BAD_EXPANSION: &str = "ERR bad expansion";
+pub const BAD_CAPACITY: &str = "ERR bad capacity";
+pub const BAD_LAYOUT: &str = "ERR bad layout";
+pub const BAD_HASH_FUNCTION: &str = "ERR bad hash function";
+pub const BAD_ERROR_RATE: &str = "ERR bad error rate";
+pub const ERROR_RATE_RANGE: &str = "ERR (0 < error rate range < 1)";
+pub const CAPACITY_LARGER_THAN_0: &str = "ERR (capacity should be larger than 0)";
+pub const MAX_NUM_SCALING_FILTERS: &str = "ERR max number of scaling filters reached";
+pub const UNKNOWN_ARGUMENT: &str = "ERR unknown argument received";
+pub const ATOMIC_AND_CONTINUE: &str = "ERR ATOMIC and CONTINUE cannot be used together";
+pub const BAD_ITERATOR: &str = "ERR bad iterator";
+pub const BAD_SCANDUMP_DATA: &str = "ERR received bad data";
+pub const INVALID_CHUNK_OFFSET: &str = "ERR invalid offset - no link found";
+pub const CHUNK_TOO_BIG: &str = "ERR invalid chunk - Too big for current filter";
+pub const INCOMPATIBLE_FILTERS: &str =
+    "ERR bloom objects have incompatible filters (layout, hash function or filter sizes differ)";
+pub const EXCEEDS_MAX_BLOOM_SIZE: &str = "ERR operation exceeds bloom object memory limit";
+pub const CROSS_SLOT: &str = "CROSSSLOT Keys in request don't hash to the same slot";
+
+/// Errors of the bloom commands, from parsing and validating the arguments to adding items.
+/// Every error is replied to the client with its message from `as_str`. A key of another type is replied
+/// to with the standard WRONGTYPE error of the server.
+#[derive(Debug, PartialEq)]
+pub enum BloomError {
+    WrongType,
+    NonScalingFilterFull,
+    NotFound,
+    ItemExists,
+    InvalidInfoValue,
+    BadExpansion,
+    BadCapacity,
+    BadLayout,
+    BadHashFunction,
+    BadErrorRate,
+    ErrorRateRange,
+    CapacityLargerThan0,
+    MaxNumScalingFilters,
+    UnknownArgument,
+    AtomicAndContinue,
+    IncompatibleFilters,
+    BadIterator,
+    BadScanDumpData,
+    InvalidChunkOffset,
+    ChunkTooBig,
+    ExceedsMaxBloomSize,
+    CrossSlot,
+}
+
+impl BloomError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BloomError::WrongType => WRONG_TYPE,
+            BloomError::NonScalingFilterFull => NON_SCALING_FILTER_FULL,
+            BloomError::NotFound => NOT_FOUND,
+            BloomError::ItemExists => ITEM_EXISTS,
+            BloomError::InvalidInfoValue => INVALID_INFO_VALUE,
+            BloomError::BadExpansion => BAD_EXPANSION,
+            BloomError::BadCapacity => BAD_CAPACITY,
+            BloomError::BadLayout => BAD_LAYOUT,
+            BloomError::BadHashFunction => BAD_HASH_FUNCTION,
+            BloomError::BadErrorRate => BAD_ERROR_RATE,
+            BloomError::ErrorRateRange => ERROR_RATE_RANGE,
+            BloomError::CapacityLargerThan0 => CAPACITY_LARGER_THAN_0,
+            BloomError::MaxNumScalingFilters => MAX_NUM_SCALING_FILTERS,
+            BloomError::UnknownArgument => UNKNOWN_ARGUMENT,
+            BloomError::AtomicAndContinue => ATOMIC_AND_CONTINUE,
+            BloomError::IncompatibleFilters => INCOMPATIBLE_FILTERS,
+            BloomError::BadIterator => BAD_ITERATOR,
+            BloomError::BadScanDumpData => BAD_SCANDUMP_DATA,
+            BloomError::InvalidChunkOffset => INVALID_CHUNK_OFFSET,
+            BloomError::ChunkTooBig => CHUNK_TOO_BIG,
+            BloomError::ExceedsMaxBloomSize => EXCEEDS_MAX_BLOOM_SIZE,
+            BloomError::CrossSlot => CROSS_SLOT,
+        }
+    }
+}
+
+#[cfg(feature = "valkey-module")]
+impl From<BloomError> for ValkeyError {
+    fn from(err: BloomError) -> ValkeyError {
+        match err {
+            BloomError::WrongType => ValkeyError::WrongType,
+            _ => ValkeyError::Str(err.as_str()),
+        }
+    }
+}
+
+/// The layout of the bits of every filter in a BloomFilterType object.
+/// Standard filters spread the bits of an item across the entire bitmap.
+/// Blocked filters place all the bits of an item within one cache line sized block of the bitmap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BloomFilterLayout {
+    Standard,
+    Blocked,
+    RedisBloom,
+}
+
+impl BloomFilterLayout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BloomFilterLayout::Standard => "standard",
+            BloomFilterLayout::Blocked => "blocked",
+            BloomFilterLayout::RedisBloom => "redisbloom",
+        }
+    }
+
+    /// Parse the layout name provided in a command (case insensitive).
+    pub fn from_name(name: &str) -> Option<BloomFilterLayout> {
+        match name.to_uppercase().as_str() {
+            "STANDARD" => Some(BloomFilterLayout::Standard),
+            "BLOCKED" => Some(BloomFilterLayout::Blocked),
+            "REDISBLOOM" => Some(BloomFilterLayout::RedisBloom),
+            _ => None,
+        }
+    }
+
+    /// Returns the value used to encode the layout in RDB.
+    pub fn encoding(&self) -> u64 {
+        match self {
+            BloomFilterLayout::Standard => 0,
+            BloomFilterLayout::Blocked => 1,
+            BloomFilterLayout::RedisBloom => 2,
+        }
+    }
+
+    /// Returns the layout from the value it was encoded with in RDB.
+    pub fn from_encoding(value: u64) -> Option<BloomFilterLayout> {
+        match value {
+            0 => Some(BloomFilterLayout::Standard),
+            1 => Some(BloomFilterLayout::Blocked),
+            2 => Some(BloomFilterLayout::RedisBloom),
+            _ => None,
+        }
+    }
+}
+
+/// The hash function used to map items to the bits of every filter in a BloomFilterType object.
+/// SipHash (with the fixed sip keys) is the default and is used by all objects created before
+/// the hash function became configurable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BloomHashFunction {
+    SipHash,
+    XxHash3,
+    MurmurHash3,
+    MurmurHash64A,
+    MurmurHash2,
+}
+
+impl BloomHashFunction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BloomHashFunction::SipHash => "siphash",
+            BloomHashFunction::XxHash3 => "xxhash3",
+            BloomHashFunction::MurmurHash3 => "murmurhash3",
+            BloomHashFunction::MurmurHash64A => "murmurhash64a",
+            BloomHashFunction::MurmurHash2 => "murmurhash2",
+        }
+    }
+
+    /// Parse the hash function name provided in a command (case insensitive).
+    pub fn from_name(name: &str) -> Option<BloomHashFunction> {
+        match name.to_uppercase().as_str() {
+            "SIPHASH" => Some(BloomHashFunction::SipHash),
+            "XXHASH3" => Some(BloomHashFunction::XxHash3),
+            "MURMURHASH3" => Some(BloomHashFunction::MurmurHash3),
+            "MURMURHASH64A" => Some(BloomHashFunction::MurmurHash64A),
+            "MURMURHASH2" => Some(BloomHashFunction::MurmurHash2),
+            _ => None,
+        }
+    }
+
+    /// Returns the value used to encode the hash function in RDB.
+    pub fn encoding(&self) -> u64 {
+        match self {
+            BloomHashFunction::SipHash => 0,
+            BloomHashFunction::XxHash3 => 1,
+            BloomHashFunction::MurmurHash3 => 2,
+            BloomHashFunction::MurmurHash64A => 3,
+            BloomHashFunction::MurmurHash2 => 4,
+        }
+    }
+
+    /// Returns the hash function from the value it was encoded with in RDB.
+    pub fn from_encoding(value: u64) -> Option<BloomHashFunction> {
+        match value {
+            0 => Some(BloomHashFunction::SipHash),
+            1 => Some(BloomHashFunction::XxHash3),
+            2 => Some(BloomHashFunction::MurmurHash3),
+            3 => Some(BloomHashFunction::MurmurHash64A),
+            4 => Some(BloomHashFunction::MurmurHash2),
+            _ => None,
+        }
+    }
+
+    /// Returns the two 64 bit hashes of an item from which the bit positions of the item are derived.
+    /// The SipHash hashes are the same as the ones computed by the "bloomfilter" crate, and the MurmurHash64A
+    /// and MurmurHash2 hashes are the same as the ones computed by the RedisBloom module for 64 bit and
+    /// (older) 32 bit filters.
+    pub fn hash(&self, item: &[u8]) -> (u64, u64) {
+        match self {
+            BloomHashFunction::SipHash => {
+                let mut sip_one =
+                    SipHasher13::new_with_keys(FIXED_SIP_KEY_ONE_A, FIXED_SIP_KEY_ONE_B);
+                let mut sip_two =
+                    SipHasher13::new_with_keys(FIXED_SIP_KEY_TWO_A, FIXED_SIP_KEY_TWO_B);
+                item.hash(&mut sip_one);
+                item.hash(&mut sip_two);
+                (sip_one.finish(), sip_two.finish())
+            }
+            BloomHashFunction::XxHash3 => {
+                let hash = xxhash_rust::xxh3::xxh3_128(item);
+                (hash as u64, (hash >> 64) as u64)
+            }
+            BloomHashFunction::MurmurHash3 => {
+                // Reading from a slice cannot fail.
+                let mut reader = item;
+                let hash = murmur3::murmur3_x64_128(&mut reader, 0).unwrap_or_default();
+                (hash as u64, (hash >> 64) as u64)
+            }
+            BloomHashFunction::MurmurHash64A => {
+                let hash_one = murmur_hash64a(item, 0xc6a4_a793_5bd1_e995);
+                (hash_one, murmur_hash64a(item, hash_one))
+            }
+            BloomHashFunction::MurmurHash2 => {
+                let hash_one = murmur_hash2(item, 0x9747_b28c);
+                (hash_one as u64, murmur_hash2(item, hash_one) as u64)
+            }
+        }
+    }
+}
+
+/// MurmurHash64A by Austin Appleby, reading the item in little endian 64 bit words.
+fn murmur_hash64a(item: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+    let mut hash = seed ^ (item.len() as u64).wrapping_mul(M);
+    let mut chunks = item.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap_or_default());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        hash ^= k;
+        hash = hash.wrapping_mul(M);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (idx, byte) in tail.iter().enumerate() {
+            hash ^= (*byte as u64) << (8 * idx);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+    hash ^= hash >> R;
+    hash = hash.wrapping_mul(M);
+    hash ^ (hash >> R)
+}
+
+/// MurmurHash2 (32 bit) by Austin Appleby, reading the item in little endian 32 bit words.
+fn murmur_hash2(item: &[u8], seed: u32) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+    let mut hash = seed ^ (item.len() as u32);
+    let mut chunks = item.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes(chunk.try_into().unwrap_or_default());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        hash = hash.wrapping_mul(M);
+        hash ^= k;
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (idx, byte) in tail.iter().enumerate() {
+            hash ^= (*byte as u32) << (8 * idx);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^ (hash >> 15)
+}
+
+/// The BloomFilterType structure. 40 bytes.
+/// Can contain one or more filters.
+/// This is a generic top level structure which is not coupled to any bloom crate.
+pub struct BloomFilterType {
+    pub expansion: u32,
+    pub fp_rate: f32,
+    pub layout: BloomFilterLayout,
+    pub hash_function: BloomHashFunction,
+    pub filters: Vec<BloomFilter>,
+}
+
+impl BloomFilterType {
+    /// Create a new BloomFilterType object, unless its filter would exceed the memory limit of bloom objects.
+    pub fn new_reserved(
+        fp_rate: f32,
+        capacity: u64,
+        expansion: u32,
+        layout: BloomFilterLayout,
+        hash_function: BloomHashFunction,
+    ) -> Result<BloomFilterType, BloomError> {
+        BloomFilterType::validate_size(
+            std::mem::size_of::<BloomFilterType>(),
+            fp_rate,
+            capacity,
+            layout,
+        )?;
+        let bloom = BloomFilter::new(fp_rate, capacity, layout, hash_function);
+        let filters = vec![bloom];
+        Ok(BloomFilterType::from_existing(
+            expansion,
+            fp_rate,
+            layout,
+            hash_function,
+            filters,
+        ))
+    }
+
+    /// Check that an object of `memory_usage` bytes stays within the memory limit of bloom objects once a filter
+    /// sized for the capacity and false positive rate is added to it, before the filter is allocated.
+    fn validate_size(
+        memory_usage: usize,
+        fp_rate: f32,
+        capacity: u64,
+        layout: BloomFilterLayout,
+    ) -> Result<(), BloomError> {
+        let size = (memory_usage as u64)
+            .saturating_add(BloomFilter::compute_size(fp_rate, capacity, layout));
+        if size > configs::memory_limit_per_object() {
+            return Err(BloomError::ExceedsMaxBloomSize);
+        }
+        Ok(())
+    }
+
+    /// Create a BloomFilterType object from existing filters (RDB load).
+    pub fn from_existing(
+        expansion: u32,
+        fp_rate: f32,
+        layout: BloomFilterLayout,
+        hash_function: BloomHashFunction,
+        filters: Vec<BloomFilter>,
+    ) -> BloomFilterType {
+        metrics::object_created(std::mem::size_of::<BloomFilterType>());
+        BloomFilterType {
+            expansion,
+            fp_rate,
+            layout,
+            hash_function,
+            filters,
+        }
+    }
+
+    /// Create a new BloomFilterType object from an existing one.
+    pub fn create_copy_from(from_bf: &BloomFilterType) -> BloomFilterType {
+        let mut filters = Vec::new();
+        for filter in &from_bf.filters {
+            let new_filter = BloomFilter::create_copy_from(filter);
+            filters.push(new_filter);
+        }
+        BloomFilterType::from_existing(
+            from_bf.expansion,
+            from_bf.fp_rate,
+            from_bf.layout,
+            from_bf.hash_function,
+            filters,
+        )
+    }
+
+    /// Create an empty BloomFilterType object with the properties of an existing one, holding a single
+    /// filter sized for the capacity. Used to rebuild an object which has scaled out into many filters.
+    pub fn create_compacted_from(
+        from_bf: &BloomFilterType,
+        capacity: u64,
+    ) -> Result<BloomFilterType, BloomError> {
+        BloomFilterType::new_reserved(
+            from_bf.fp_rate,
+            capacity,
+            from_bf.expansion,
+            from_bf.layout,
+            from_bf.hash_function,
+        )
+    }
+
+    /// Return the total memory usage of the BloomFilterType object.
+    pub fn memory_usage(&self) -> usize {
+        let mut mem: usize = std::mem::size_of::<BloomFilterType>();
+        for filter in &self.filters {
+            mem += filter.number_of_bytes();
+        }
+        mem
+    }
+
+    /// Returns the Bloom object's free_effort, in units of FREE_EFFORT_BYTES_PER_UNIT allocated bytes.
+    /// Objects with large bitmaps exceed the lazyfree threshold of the server and are freed in a background
+    /// thread on UNLINK, while small objects, even with many filters, are cheap enough to free right away.
+    pub fn free_effort(&self) -> usize {
+        self.memory_usage().div_ceil(FREE_EFFORT_BYTES_PER_UNIT)
+    }
+
+    /// Check whether the object is defragmented by copying it, which is only done when its free effort
+    /// is within DEFRAG_MAX_FREE_EFFORT.
+    pub fn should_defrag(&self) -> bool {
+        self.free_effort() <= DEFRAG_MAX_FREE_EFFORT
+    }
+
+    /// Check if item exists already.
+    pub fn item_exists(&self, item: &[u8]) -> bool {
+        self.filters.iter().any(|filter| filter.check(item))
+    }
+
+    /// Return a count of number of items added to all sub filters in the BloomFilterType object.
+    pub fn cardinality(&self) -> i64 {
+        let mut cardinality: i64 = 0;
+        for filter in &self.filters {
+            cardinality = cardinality.saturating_add(filter.num_items as i64);
+        }
+        cardinality
+    }
+
+    /// Return an estimate of the number of unique items added to all sub filters in the BloomFilterType object,
+    /// based on the number of bits set in every sub filter rather than the number of successful adds.
+    pub fn estimated_cardinality(&self) -> i64 {
+        let estimate: f64 = self
+            .filters
+            .iter()
+            .map(|filter| filter.estimated_num_items())
+            .sum();
+        estimate.round() as i64
+    }
+
+    /// Return an estimate of the number of unique items which were added to both BloomFilterType objects.
+    /// Only sub filters of identical geometry (same number of bits, hash functions, layout and hash function)
+    /// can be intersected, so every such pair of sub filters is intersected and the estimates are summed.
+    /// Items which landed in sub filters of different sizes in the two objects are not counted.
+    /// Objects with a different layout or hash function, or without any pair of sub filters of identical
+    /// geometry, are incompatible.
+    pub fn estimated_intersection_cardinality(
+        &self,
+        other: &BloomFilterType,
+    ) -> Result<i64, BloomError> {
+        if self.layout != other.layout || self.hash_function != other.hash_function {
+            return Err(BloomError::IncompatibleFilters);
+        }
+        let mut compatible = false;
+        let mut estimate: f64 = 0.0;
+        for filter in &self.filters {
+            for other_filter in &other.filters {
+                if filter.has_same_geometry(other_filter) {
+                    compatible = true;
+                    estimate += filter.estimated_num_common_items(other_filter);
+                }
+            }
+        }
+        if !compatible {
+            return Err(BloomError::IncompatibleFilters);
+        }
+        Ok(estimate.max(0.0).round() as i64)
+    }
+
+    /// Merge the items of another BloomFilterType object into this one. Every sub filter is combined bit by bit
+    /// with the sub filter at the same position of the other object, and sub filters which the other object has
+    /// scaled out into beyond the ones of this object are copied over.
+    /// Objects with a different layout or hash function, or with sub filters at the same position which do not
+    /// have the same geometry, are incompatible and leave this object unchanged. So are objects with more sub
+    /// filters when the last sub filter of this object would not be full once merged, as only the number of items
+    /// of the last sub filter is persisted and the ones before it are loaded as full. The sub filters to copy must
+    /// also fit within the memory limit of bloom objects.
+    pub fn merge_from(&mut self, other: &BloomFilterType) -> Result<(), BloomError> {
+        if self.layout != other.layout
+            || self.hash_function != other.hash_function
+            || !self
+                .filters
+                .iter()
+                .zip(other.filters.iter())
+                .all(|(filter, other_filter)| filter.has_same_geometry(other_filter))
+        {
+            return Err(BloomError::IncompatibleFilters);
+        }
+        if let Some(last_filter) = self.filters.last() {
+            if other.filters.len() > self.filters.len()
+                && last_filter.num_items < last_filter.capacity
+                && other.filters[self.filters.len() - 1].num_items < last_filter.capacity
+            {
+                return Err(BloomError::IncompatibleFilters);
+            }
+        }
+        if other.filters.len() > self.filters.len()
+            && other.filters.len() as i32 > configs::max_filters_per_object()
+        {
+            return Err(BloomError::MaxNumScalingFilters);
+        }
+        let copied_bytes = other
+            .filters
+            .iter()
+            .skip(self.filters.len())
+            .fold(0_u64, |size, filter| {
+                size.saturating_add(filter.number_of_bytes() as u64)
+            });
+        if copied_bytes > 0
+            && (self.memory_usage() as u64).saturating_add(copied_bytes)
+                > configs::memory_limit_per_object()
+        {
+            return Err(BloomError::ExceedsMaxBloomSize);
+        }
+        for (filter, other_filter) in self.filters.iter_mut().zip(other.filters.iter()) {
+            filter.merge_from(other_filter);
+        }
+        for other_filter in other.filters.iter().skip(self.filters.len()) {
+            self.filters
+                .push(BloomFilter::create_copy_from(other_filter));
+        }
+        Ok(())
+    }
+
+    /// Return a total capacity summed across all sub filters in the BloomFilterType object.
+    pub fn capacity(&self) -> i64 {
+        let mut capacity: i64 = 0;
+        // Check if item exists already.
+        for filter in &self.filters {
+            capacity = capacity.saturating_add(filter.capacity as i64);
+        }
+        capacity
+    }
+
+    /// Remove all items from the BloomFilterType object.
+    /// Scaled out sub filters are dropped and the first filter is emptied in place, so the object keeps
+    /// its false positive rate, expansion and initial capacity.
+    pub fn clear(&mut self) {
+        self.filters.truncate(1);
+        if let Some(filter) = self.filters.first_mut() {
+            filter.clear();
+        }
+    }
+
+    /// Check whether the number of new items can be added to the BloomFilterType object, scaling out if
+    /// needed, without filling a non scaling filter or reaching the limits on the number of sub filters and memory.
+    /// This mirrors the scaling of `add_item`, so that a batch of items can be validated before any of them is added.
+    pub fn validate_capacity_for(&self, num_new_items: u64) -> Result<(), BloomError> {
+        let last_filter = match self.filters.last() {
+            Some(filter) => filter,
+            None => return Ok(()),
+        };
+        // Loaded filters can hold more items than their capacity, which leaves no room in them.
+        let mut remaining_items = num_new_items
+            .saturating_sub(last_filter.capacity.saturating_sub(last_filter.num_items));
+        if remaining_items == 0 {
+            return Ok(());
+        }
+        if self.expansion == 0 {
+            return Err(BloomError::NonScalingFilterFull);
+        }
+        let max_filters = configs::max_filters_per_object();
+        let mut num_filters = self.filters.len() as i32;
+        let mut capacity = last_filter.capacity;
+        let mut memory_usage = self.memory_usage();
+        while remaining_items > 0 {
+            if num_filters >= max_filters {
+                return Err(BloomError::MaxNumScalingFilters);
+            }
+            let fp_rate = self.fp_rate * TIGHTENING_RATIO.powi(num_filters);
+            capacity = capacity.saturating_mul(self.expansion as u64);
+            BloomFilterType::validate_size(memory_usage, fp_rate, capacity, self.layout)?;
+            memory_usage += BloomFilter::compute_size(fp_rate, capacity, self.layout) as usize;
+            num_filters += 1;
+            remaining_items = remaining_items.saturating_sub(capacity);
+        }
+        Ok(())
+    }
+
+    /// Add an item to the BloomFilterType object.
+    /// If scaling is enabled, this can result in a new sub filter creation.
+    pub fn add_item(&mut self, item: &[u8]) -> Result<i64, BloomError> {
+        // Check if item exists already.
+        if self.item_exists(item) {
+            return Ok(0);
+        }
+        let num_filters = self.filters.len() as i32;
+        let Some(filter) = self.filters.last_mut() else {
+            return Ok(0);
+        };
+        if filter.num_items < filter.capacity {
+            // Add item.
+            filter.set(item);
+            filter.num_items += 1;
+            return Ok(1);
+        }
+        // Non Scaling Filters that are filled to capacity cannot handle more inserts.
+        if self.expansion == 0 {
+            return Err(BloomError::NonScalingFilterFull);
+        }
+        // The limit can be lowered below the number of filters of existing objects, which then stop scaling out.
+        if num_filters >= configs::max_filters_per_object() {
+            return Err(BloomError::MaxNumScalingFilters);
+        }
+        // Scale out by adding a new filter with capacity bounded within the u64 range.
+        let new_fp_rate = self.fp_rate * TIGHTENING_RATIO.powi(num_filters);
+        let new_capacity = filter.capacity.saturating_mul(self.expansion as u64);
+        BloomFilterType::validate_size(
+            self.memory_usage(),
+            new_fp_rate,
+            new_capacity,
+            self.layout,
+        )?;
+        let mut new_filter =
+            BloomFilter::new(new_fp_rate, new_capacity, self.layout, self.hash_function);
+        // Add item.
+        new_filter.set(item);
+        new_filter.num_items += 1;
+        self.filters.push(new_filter);
+        Ok(1)
+    }
+
+    /// Returns the header of the object replied by BF.SCANDUMP, in the packed little endian layout of RedisBloom:
+    /// the number of items (u64), filters (u32), options (u32) and growth (u32), followed for every filter by
+    /// its number of bytes (u64), bits (u64) and items (u64), error rate (f64), bits per item (f64), number of
+    /// hash functions (u32), capacity (u64) and power of two of the number of bits (u8, 0 as it is not rounded).
+    /// Objects which RedisBloom cannot hash (other layouts or hash functions) are flagged with a bit of the options
+    /// that RedisBloom does not use, with their layout and hash function encodings in the upper bytes.
+    pub fn encoded_header(&self) -> Vec<u8> {
+        let mut options = REDISBLOOM_OPT_NOROUND as u32;
+        if self.expansion == 0 {
+            options |= REDISBLOOM_OPT_NO_SCALING as u32;
+        }
+        match (self.layout, self.hash_function) {
+            (BloomFilterLayout::RedisBloom, BloomHashFunction::MurmurHash64A) => {
+                options |= REDISBLOOM_OPT_FORCE64 as u32;
+            }
+            (BloomFilterLayout::RedisBloom, BloomHashFunction::MurmurHash2) => {}
+            (layout, hash_function) => {
+                options |= SCANDUMP_OPT_VALKEY_HASHING
+                    | (layout.encoding() as u32) << 16
+                    | (hash_function.encoding() as u32) << 24;
+            }
+        }
+        let mut header =
+            Vec::with_capacity(SCANDUMP_HEADER_SIZE + self.filters.len() * SCANDUMP_FILTER_SIZE);
+        header.extend_from_slice(&(self.cardinality() as u64).to_le_bytes());
+        header.extend_from_slice(&(self.filters.len() as u32).to_le_bytes());
+        header.extend_from_slice(&options.to_le_bytes());
+        header.extend_from_slice(&self.expansion.max(1).to_le_bytes());
+        let mut fp_rate = self.fp_rate as f64;
+        for filter in &self.filters {
+            header.extend_from_slice(&(filter.bloom.bitmap_len() as u64).to_le_bytes());
+            header.extend_from_slice(&filter.bloom.number_of_bits().to_le_bytes());
+            header.extend_from_slice(&filter.num_items.to_le_bytes());
+            header.extend_from_slice(&fp_rate.to_le_bytes());
+            header.extend_from_slice(&CompatBloom::bits_per_item(fp_rate).to_le_bytes());
+            header.extend_from_slice(&filter.bloom.number_of_hash_functions().to_le_bytes());
+            header.extend_from_slice(&filter.capacity.to_le_bytes());
+            header.push(0);
+            fp_rate *= TIGHTENING_RATIO as f64;
+        }
+        header
+    }
+
+    /// Create an object with empty filters from a header replied by BF.SCANDUMP, by valkey-bloom or RedisBloom.
+    /// The bitmaps of the filters are then loaded chunk by chunk with `load_encoded_chunk`.
+    /// With `check_memory_limit`, headers whose filters exceed the memory limit of bloom objects are rejected. Headers
+    /// replayed from the AOF or the primary are not validated, as their objects may have been loaded from an RDB
+    /// or created before the limit was lowered, and rejecting them would drop the key.
+    pub fn from_encoded_header(
+        header: &[u8],
+        check_memory_limit: bool,
+    ) -> Result<BloomFilterType, BloomError> {
+        let mut reader = header;
+        let mut read = |len: usize| -> Result<&[u8], BloomError> {
+            if reader.len() < len {
+                return Err(BloomError::BadScanDumpData);
+            }
+            let (field, rest) = reader.split_at(len);
+            reader = rest;
+            Ok(field)
+        };
+        let read_u64 = |field: &[u8]| u64::from_le_bytes(field.try_into().unwrap_or_default());
+        let read_u32 = |field: &[u8]| u32::from_le_bytes(field.try_into().unwrap_or_default());
+        let read_f64 = |field: &[u8]| f64::from_le_bytes(field.try_into().unwrap_or_default());
+        let _num_items = read_u64(read(8)?);
+        let num_filters = read_u32(read(4)?) as usize;
+        let options = read_u32(read(4)?);
+        let growth = read_u32(read(4)?);
+        if header.len() != SCANDUMP_HEADER_SIZE + num_filters * SCANDUMP_FILTER_SIZE
+            || num_filters == 0
+        {
+            return Err(BloomError::BadScanDumpData);
+        }
+        let (layout, hash_function) = if options & SCANDUMP_OPT_VALKEY_HASHING != 0 {
+            (
+                BloomFilterLayout::from_encoding(((options >> 16) & 0xff) as u64)
+                    .ok_or(BloomError::BadScanDumpData)?,
+                BloomHashFunction::from_encoding((options >> 24) as u64)
+                    .ok_or(BloomError::BadScanDumpData)?,
+            )
+        } else if options as u64 & REDISBLOOM_OPT_FORCE64 != 0 {
+            (
+                BloomFilterLayout::RedisBloom,
+                BloomHashFunction::MurmurHash64A,
+            )
+        } else {
+            (
+                BloomFilterLayout::RedisBloom,
+                BloomHashFunction::MurmurHash2,
+            )
+        };
+        let expansion = if options as u64 & REDISBLOOM_OPT_NO_SCALING != 0 {
+            0
+        } else {
+            growth
+        };
+        // Every filter is validated before any bitmap is allocated, so that the sizes received from the client
+        // cannot exceed the memory limit of bloom objects.
+        let mut fp_rate = None;
+        let mut memory_usage = std::mem::size_of::<BloomFilterType>() as u64;
+        let mut filter_params = Vec::with_capacity(num_filters);
+        for _ in 0..num_filters {
+            let number_of_bytes = read_u64(read(8)?);
+            let number_of_bits = read_u64(read(8)?);
+            let num_items = read_u64(read(8)?);
+            let error = read_f64(read(8)?);
+            let _bits_per_item = read_f64(read(8)?);
+            let number_of_hash_functions = read_u32(read(4)?);
+            let capacity = read_u64(read(8)?);
+            let _n2 = read(1)?;
+            // Other layouts than the one of RedisBloom rebuild their bitmap of exactly the number of bits.
+            let expected_bytes = match layout {
+                BloomFilterLayout::RedisBloom => number_of_bytes.max(number_of_bits.div_ceil(8)),
+                _ => number_of_bits.div_ceil(8),
+            };
+            if number_of_bits == 0
+                || number_of_bytes != expected_bytes
+                || (layout == BloomFilterLayout::Blocked && number_of_bits % BLOCK_BITS != 0)
+                || number_of_hash_functions == 0
+                || capacity == 0
+                || !(error > 0.0 && error < 1.0)
+            {
+                return Err(BloomError::BadScanDumpData);
+            }
+            fp_rate.get_or_insert(error as f32);
+            memory_usage = memory_usage
+                .saturating_add(std::mem::size_of::<BloomFilter>() as u64)
+                .saturating_add(number_of_bytes);
+            filter_params.push((
+                number_of_bytes,
+                number_of_bits,
+                number_of_hash_functions,
+                num_items,
+                capacity,
+            ));
+        }
+        if check_memory_limit && memory_usage > configs::memory_limit_per_object() {
+            return Err(BloomError::ExceedsMaxBloomSize);
+        }
+        let filters = filter_params
+            .into_iter()
+            .map(
+                |(
+                    number_of_bytes,
+                    number_of_bits,
+                    number_of_hash_functions,
+                    num_items,
+                    capacity,
+                )| {
+                    BloomFilter::from_existing(
+                        layout,
+                        hash_function,
+                        &vec![0; number_of_bytes as usize],
+                        number_of_bits,
+                        number_of_hash_functions,
+                        num_items,
+                        capacity,
+                    )
+                },
+            )
+            .collect();
+        Ok(BloomFilterType::from_existing(
+            expansion,
+            fp_rate.ok_or(BloomError::BadScanDumpData)?,
+            layout,
+            hash_function,
+            filters,
+        ))
+    }
+
+    /// Returns the filter holding the byte at the offset across the bitmaps of all the filters, and the offset
+    /// of the byte within the bitmap of that filter.
+    fn filter_at_offset(&self, offset: u64) -> Option<(usize, u64)> {
+        let mut filter_start = 0;
+        for (idx, filter) in self.filters.iter().enumerate() {
+            let filter_end = filter_start + filter.bloom.bitmap_len() as u64;
+            if offset < filter_end {
+                return Some((idx, offset - filter_start));
+            }
+            filter_start = filter_end;
+        }
+        None
+    }
+
+    /// Returns the chunk of the bitmaps replied by BF.SCANDUMP for the iterator, with the iterator of the next
+    /// chunk, or None once every chunk was replied. Like RedisBloom, the iterator is one more than the offset of
+    /// the chunk across the bitmaps of all the filters, and chunks do not span filters.
+    pub fn encoded_chunk(&self, iterator: u64) -> Option<(u64, Vec<u8>)> {
+        let (filter_idx, offset) = self.filter_at_offset(iterator.checked_sub(1)?)?;
+        let bloom = &self.filters[filter_idx].bloom;
+        let offset = offset as usize;
+        let len = (bloom.bitmap_len() - offset).min(SCANDUMP_MAX_CHUNK_SIZE);
+        let chunk = match bloom {
+            BloomBitmap::RedisBloom(bloom) => bloom.bytes[offset..offset + len].to_vec(),
+            _ => bloom.bitmap()[offset..offset + len].to_vec(),
+        };
+        Some((iterator + len as u64, chunk))
+    }
+
+    /// Returns the iterators and data of the BF.LOADCHUNK commands which restore the object: the header with
+    /// the iterator 1, followed by every chunk of the bitmaps with the iterator of the next chunk. Chunks are
+    /// encoded one at a time as the commands are consumed.
+    pub fn load_chunks(&self) -> impl Iterator<Item = (u64, Vec<u8>)> + '_ {
+        let header = std::iter::once((1, self.encoded_header()));
+        let chunks = std::iter::successors(self.encoded_chunk(1), move |(iterator, _)| {
+            self.encoded_chunk(*iterator)
+        });
+        header.chain(chunks)
+    }
+
+    /// Load a chunk replied by BF.SCANDUMP with the iterator of the next chunk, as BF.LOADCHUNK receives it.
+    pub fn load_encoded_chunk(&mut self, iterator: u64, chunk: &[u8]) -> Result<(), BloomError> {
+        let offset = iterator
+            .checked_sub(chunk.len() as u64 + 1)
+            .ok_or(BloomError::InvalidChunkOffset)?;
+        let (filter_idx, offset) = self
+            .filter_at_offset(offset)
+            .ok_or(BloomError::InvalidChunkOffset)?;
+        let filter = &mut self.filters[filter_idx];
+        if chunk.len() as u64 > filter.bloom.bitmap_len() as u64 - offset {
+            return Err(BloomError::ChunkTooBig);
+        }
+        filter.load_bitmap_chunk(offset as usize, chunk);
+        Ok(())
+    }
+}
+
+/// Size in bytes of the header replied by BF.SCANDUMP without its filters, and of each of its filters.
+pub const SCANDUMP_HEADER_SIZE: usize = 20;
+pub const SCANDUMP_FILTER_SIZE: usize = 53;
+/// Option of a BF.SCANDUMP header for objects which are not hashed like RedisBloom hashes them.
+pub const SCANDUMP_OPT_VALKEY_HASHING: u32 = 1 << 15;
+
+impl Drop for BloomFilterType {
+    fn drop(&mut self) {
+        metrics::object_dropped(std::mem::size_of::<BloomFilterType>());
+    }
+}
+
+// Structure representing a single bloom filter. 64 Bytes.
+// The standard layout is sized and hashed like the "bloomfilter" crate.
+// num_items and capacity are u64, as filters with a high false positive rate
+// can hold more than u32::MAX items within a small bitmap.
+pub struct BloomFilter {
+    pub bloom: BloomBitmap,
+    pub num_items: u64,
+    pub capacity: u64,
+}
+
+impl BloomFilter {
+    /// Instantiate empty BloomFilter object. The size of the filter is expected to be validated with
+    /// `compute_size` first, as the bitmap is allocated for any capacity.
+    pub fn new(
+        fp_rate: f32,
+        capacity: u64,
+        layout: BloomFilterLayout,
+        hash_function: BloomHashFunction,
+    ) -> BloomFilter {
+        let bloom = match layout {
+            BloomFilterLayout::Standard => BloomBitmap::Hashed(HashedBloom::new(
+                capacity as usize,
+                fp_rate as f64,
+                hash_function,
+            )),
+            BloomFilterLayout::Blocked => BloomBitmap::Blocked(BlockedBloom::new(
+                capacity as usize,
+                fp_rate as f64,
+                hash_function,
+            )),
+            BloomFilterLayout::RedisBloom => {
+                BloomBitmap::RedisBloom(CompatBloom::new(capacity, fp_rate as f64, hash_function))
+            }
+        };
+        let filter = BloomFilter {
+            bloom,
+            num_items: 0,
+            capacity,
+        };
+        metrics::filter_created(filter.number_of_bytes());
+        filter
+    }
+
+    /// Create a new BloomFilter from dumped information (RDB load).
+    pub fn from_existing(
+        layout: BloomFilterLayout,
+        hash_function: BloomHashFunction,
+        bitmap: &[u8],
+        number_of_bits: u64,
+        number_of_hash_functions: u32,
+        num_items: u64,
+        capacity: u64,
+    ) -> BloomFilter {
+        let bloom = match layout {
+            BloomFilterLayout::Standard => BloomBitmap::Hashed(HashedBloom::from_existing(
+                bitmap,
+                number_of_bits,
+                number_of_hash_functions,
+                hash_function,
+            )),
+            BloomFilterLayout::Blocked => BloomBitmap::Blocked(BlockedBloom::from_existing(
+                bitmap,
+                number_of_bits,
+                number_of_hash_functions,
+                hash_function,
+            )),
+            BloomFilterLayout::RedisBloom => BloomBitmap::RedisBloom(CompatBloom::from_existing(
+                bitmap,
+                number_of_bits,
+                number_of_hash_functions,
+                hash_function,
+            )),
+        };
+        let filter = BloomFilter {
+            bloom,
+            num_items,
+            capacity,
+        };
+        metrics::filter_created(filter.number_of_bytes());
+        filter
+    }
+
+    /// Returns the number of bytes of a filter sized for the capacity and false positive rate, like `number_of_bytes`
+    /// once it is created. The size is computed in floating point and saturates, rather than overflowing like the
+    /// allocation would for huge capacities or false positive rates tightened down to 0.
+    pub fn compute_size(fp_rate: f32, capacity: u64, layout: BloomFilterLayout) -> u64 {
+        let fp_rate = fp_rate as f64;
+        let capacity = capacity as f64;
+        let bitmap_size = match layout {
+            BloomFilterLayout::Standard => {
+                let ln2 = std::f64::consts::LN_2;
+                (capacity * fp_rate.ln() / (-8.0 * ln2 * ln2)).ceil() as u64
+            }
+            BloomFilterLayout::Blocked => {
+                let (bits_per_item, _) = BlockedBloom::optimal_parameters(fp_rate);
+                let number_of_blocks = ((capacity * bits_per_item).ceil() / BLOCK_BITS as f64)
+                    .ceil()
+                    .max(1.0);
+                (number_of_blocks * (BLOCK_BITS / 8) as f64) as u64
+            }
+            BloomFilterLayout::RedisBloom => ((capacity * CompatBloom::bits_per_item(fp_rate))
+                as u64)
+                .max(1)
+                .div_ceil(8),
+        };
+        bitmap_size.saturating_add(std::mem::size_of::<BloomFilter>() as u64)
+    }
+
+    pub fn number_of_bytes(&self) -> usize {
+        std::mem::size_of::<BloomFilter>() + (self.bloom.number_of_bits() / 8) as usize
+    }
+
+    /// Overwrite the bytes of the bitmap at the offset with a chunk, in place, so that loading the chunks of a
+    /// large bitmap one at a time costs the size of each chunk rather than of the bitmap.
+    pub fn load_bitmap_chunk(&mut self, offset: usize, chunk: &[u8]) {
+        match &mut self.bloom {
+            BloomBitmap::Hashed(bloom) => load_bit_vec_bytes(&mut bloom.bit_vec, offset, chunk),
+            BloomBitmap::Blocked(bloom) => bloom.load_bitmap_chunk(offset, chunk),
+            BloomBitmap::RedisBloom(bloom) => {
+                bloom.bytes[offset..offset + chunk.len()].copy_from_slice(chunk);
+            }
+        }
+    }
+
+    pub fn check(&self, item: &[u8]) -> bool {
+        self.bloom.check(item)
+    }
+
+    pub fn set(&mut self, item: &[u8]) {
+        self.bloom.set(item)
+    }
+
+    /// Estimate the number of items added to the filter from the number of set bits X, using the
+    /// Swamidass-Baldi formula: n = -(m / k) * ln(1 - X / m), where m is the number of bits and k is the
+    /// number of hash functions. A filter with every bit set is estimated as if one bit was unset.
+    pub fn estimated_num_items(&self) -> f64 {
+        let number_of_bits = self.bloom.number_of_bits() as f64;
+        let number_of_hash_functions = self.bloom.number_of_hash_functions() as f64;
+        let number_of_set_bits = (self.bloom.number_of_set_bits() as f64).min(number_of_bits - 1.0);
+        -(number_of_bits / number_of_hash_functions)
+            * (1.0 - number_of_set_bits / number_of_bits).ln()
+    }
+
+    /// Check whether the bitmaps of both filters map every item to the same bit positions, which is
+    /// required to combine them bit by bit.
+    pub fn has_same_geometry(&self, other: &BloomFilter) -> bool {
+        self.bloom.layout() == other.bloom.layout()
+            && self.bloom.hash_function() == other.bloom.hash_function()
+            && self.bloom.number_of_bits() == other.bloom.number_of_bits()
+            && self.bloom.number_of_hash_functions() == other.bloom.number_of_hash_functions()
+    }
+
+    /// Estimate the number of items added to both filters from the number of bits set in both bitmaps
+    /// (the AND of the bitmaps) along with the number of bits set in each of them, using the formula of
+    /// Papapetrou et al: n = (ln(m - (X_and * m - X_1 * X_2) / (m - X_1 - X_2 + X_and)) - ln(m)) / (k * ln(1 - 1 / m)).
+    /// Both filters are expected to have the same geometry.
+    pub fn estimated_num_common_items(&self, other: &BloomFilter) -> f64 {
+        let number_of_bits = self.bloom.number_of_bits() as f64;
+        let number_of_hash_functions = self.bloom.number_of_hash_functions() as f64;
+        let set_bits_one = self.bloom.number_of_set_bits() as f64;
+        let set_bits_two = other.bloom.number_of_set_bits() as f64;
+        let common_set_bits = self
+            .bloom
+            .words()
+            .number_of_common_set_bits(&other.bloom.words()) as f64;
+        // Bits which are unset in both bitmaps. At least one is kept to estimate saturated filters.
+        let unset_bits = (number_of_bits - set_bits_one - set_bits_two + common_set_bits).max(1.0);
+        let remaining = (number_of_bits
+            - (common_set_bits * number_of_bits - set_bits_one * set_bits_two) / unset_bits)
+            .clamp(1.0, number_of_bits);
+        (remaining.ln() - number_of_bits.ln())
+            / (number_of_hash_functions * (1.0 - 1.0 / number_of_bits).ln())
+    }
+
+    /// Set every bit which is set in the bitmap of another filter of the same geometry, so that the filter holds
+    /// the items of both. Items added to both filters are counted once, so the number of items is estimated from
+    /// the merged bitmap, bounded by the number of items of either filter and the capacity.
+    pub fn merge_from(&mut self, other: &BloomFilter) {
+        let num_items = self.num_items.max(other.num_items);
+        self.bloom.union_with(&other.bloom);
+        self.num_items = (self.estimated_num_items().round() as u64)
+            .max(num_items)
+            .min(self.capacity);
+    }
+
+    /// Unset all the bits of the filter and reset the number of items.
+    pub fn clear(&mut self) {
+        self.bloom.clear();
+        self.num_items = 0;
+    }
+
+    /// Create a new BloomFilter from an existing BloomFilter object (COPY command).
+    pub fn create_copy_from(bf: &BloomFilter) -> BloomFilter {
+        BloomFilter::from_existing(
+            bf.bloom.layout(),
+            bf.bloom.hash_function(),
+            &bf.bloom.bitmap(),
+            bf.bloom.number_of_bits(),
+            bf.bloom.number_of_hash_functions(),
+            bf.num_items,
+            bf.capacity,
+        )
+    }
+}
+
+impl Drop for BloomFilter {
+    fn drop(&mut self) {
+        metrics::filter_dropped(self.number_of_bytes());
+    }
+}
+
+/// The bitmap of a single `BloomFilter` which is stored based on the layout of the object.
+pub enum BloomBitmap {
+    Hashed(HashedBloom),
+    Blocked(BlockedBloom),
+    RedisBloom(CompatBloom),
+}
+
+impl BloomBitmap {
+    pub fn layout(&self) -> BloomFilterLayout {
+        match self {
+            BloomBitmap::Hashed(_) => BloomFilterLayout::Standard,
+            BloomBitmap::Blocked(_) => BloomFilterLayout::Blocked,
+            BloomBitmap::RedisBloom(_) => BloomFilterLayout::RedisBloom,
+        }
+    }
+
+    pub fn hash_function(&self) -> BloomHashFunction {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.hash_function,
+            BloomBitmap::Blocked(bloom) => bloom.hash_function,
+            BloomBitmap::RedisBloom(bloom) => bloom.hash_function,
+        }
+    }
+
+    pub fn check(&self, item: &[u8]) -> bool {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.check(item),
+            BloomBitmap::Blocked(bloom) => bloom.check(item),
+            BloomBitmap::RedisBloom(bloom) => bloom.check(item),
+        }
+    }
+
+    pub fn set(&mut self, item: &[u8]) {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.set(item),
+            BloomBitmap::Blocked(bloom) => bloom.set(item),
+            BloomBitmap::RedisBloom(bloom) => bloom.set(item),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.clear(),
+            BloomBitmap::Blocked(bloom) => bloom.clear(),
+            BloomBitmap::RedisBloom(bloom) => bloom.clear(),
+        }
+    }
+
+    pub fn bitmap(&self) -> Vec<u8> {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.bitmap(),
+            BloomBitmap::Blocked(bloom) => bloom.bitmap(),
+            BloomBitmap::RedisBloom(bloom) => bloom.bitmap(),
+        }
+    }
+
+    /// Returns a view of the words the bitmap is stored in, without copying it.
+    pub fn words(&self) -> BitmapWords<'_> {
+        match self {
+            BloomBitmap::Hashed(bloom) => BitmapWords::U32(bloom.bit_vec.storage()),
+            BloomBitmap::Blocked(bloom) => BitmapWords::U64(&bloom.blocks),
+            BloomBitmap::RedisBloom(bloom) => BitmapWords::Bytes(&bloom.bytes),
+        }
+    }
+
+    /// Set every bit which is set in another bitmap of the same geometry.
+    pub fn union_with(&mut self, other: &BloomBitmap) {
+        match (self, other) {
+            (BloomBitmap::Hashed(bloom), BloomBitmap::Hashed(other)) => {
+                bloom.bit_vec.or(&other.bit_vec);
+            }
+            (BloomBitmap::Blocked(bloom), BloomBitmap::Blocked(other)) => {
+                for (word, other_word) in bloom.blocks.iter_mut().zip(&other.blocks) {
+                    *word |= other_word;
+                }
+            }
+            (BloomBitmap::RedisBloom(bloom), BloomBitmap::RedisBloom(other)) => {
+                for (byte, other_byte) in bloom.bytes.iter_mut().zip(&other.bytes) {
+                    *byte |= other_byte;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of bytes of the bitmap, without copying it.
+    pub fn bitmap_len(&self) -> usize {
+        match self {
+            BloomBitmap::RedisBloom(bloom) => bloom.bytes.len(),
+            _ => self.number_of_bits().div_ceil(8) as usize,
+        }
+    }
+
+    pub fn number_of_bits(&self) -> u64 {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.number_of_bits(),
+            BloomBitmap::Blocked(bloom) => bloom.number_of_bits(),
+            BloomBitmap::RedisBloom(bloom) => bloom.number_of_bits(),
+        }
+    }
+
+    pub fn number_of_set_bits(&self) -> u64 {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.number_of_set_bits(),
+            BloomBitmap::Blocked(bloom) => bloom.number_of_set_bits(),
+            BloomBitmap::RedisBloom(bloom) => bloom.number_of_set_bits(),
+        }
+    }
+
+    pub fn number_of_hash_functions(&self) -> u32 {
+        match self {
+            BloomBitmap::Hashed(bloom) => bloom.number_of_hash_functions(),
+            BloomBitmap::Blocked(bloom) => bloom.number_of_hash_functions(),
+            BloomBitmap::RedisBloom(bloom) => bloom.number_of_hash_functions(),
+        }
+    }
+}
+
+/// Borrowed view of the words a bitmap is stored in. Bitmaps of the same geometry store the same bits in words of
+/// the same size, so they can be combined word by word without copying them.
+pub enum BitmapWords<'a> {
+    Bytes(&'a [u8]),
+    U32(&'a [u32]),
+    U64(&'a [u64]),
+}
+
+impl BitmapWords<'_> {
+    /// Returns the number of bits set in both views, or 0 when they are not stored in words of the same size.
+    pub fn number_of_common_set_bits(&self, other: &BitmapWords) -> u64 {
+        match (self, other) {
+            (BitmapWords::Bytes(words), BitmapWords::Bytes(other)) => common_set_bits(words, other),
+            (BitmapWords::U32(words), BitmapWords::U32(other)) => common_set_bits(words, other),
+            (BitmapWords::U64(words), BitmapWords::U64(other)) => common_set_bits(words, other),
+            _ => 0,
+        }
+    }
+}
+
+fn common_set_bits<T: Copy + Into<u64>>(words: &[T], other: &[T]) -> u64 {
+    words
+        .iter()
+        .zip(other)
+        .map(|(word, other_word)| ((*word).into() & (*other_word).into()).count_ones() as u64)
+        .sum()
+}
+
+/// Overwrite the bytes of a bit vector at the offset, with the first bit of every byte being its most
+/// significant bit, as `BitVec::from_bytes` and `BitVec::to_bytes` order them.
+fn load_bit_vec_bytes(bit_vec: &mut BitVec, offset: usize, bytes: &[u8]) {
+    for (idx, byte) in bytes.iter().enumerate() {
+        for bit in 0..8 {
+            let pos = (offset + idx) * 8 + bit;
+            if pos < bit_vec.len() {
+                bit_vec.set(pos, byte & (0x80 >> bit) != 0);
+            }
+        }
+    }
+}
+
+/// Bloom filter with the standard layout. Sizing and bit positions follow the "bloomfilter" crate, with the two
+/// SipHash hashes replaced by the two hashes of the configured hash function, so that filters hashed with SipHash
+/// remain compatible with objects created by the crate before other layouts and hash functions were supported.
+/// The bits are held directly rather than in a `bloomfilter::Bloom`, so they can be loaded and merged in place.
+pub struct HashedBloom {
+    bit_vec: BitVec,
+    number_of_bits: u64,
+    number_of_hash_functions: u32,
+    hash_function: BloomHashFunction,
+}
+
+impl HashedBloom {
+    /// Instantiate an empty HashedBloom sized for the capacity and false positive rate.
+    pub fn new(capacity: usize, fp_rate: f64, hash_function: BloomHashFunction) -> HashedBloom {
+        let bitmap_size = bloomfilter::Bloom::<[u8]>::compute_bitmap_size(capacity, fp_rate);
+        let number_of_bits = bitmap_size as u64 * 8;
+        let number_of_hash_functions =
+            ((number_of_bits as f64 / capacity as f64 * std::f64::consts::LN_2).ceil() as u32)
+                .max(1);
+        HashedBloom {
+            bit_vec: BitVec::from_elem(number_of_bits as usize, false),
+            number_of_bits,
+            number_of_hash_functions,
+            hash_function,
+        }
+    }
+
+    /// Create a HashedBloom from an existing bitmap (RDB load).
+    pub fn from_existing(
+        bitmap: &[u8],
+        number_of_bits: u64,
+        number_of_hash_functions: u32,
+        hash_function: BloomHashFunction,
+    ) -> HashedBloom {
+        HashedBloom {
+            bit_vec: BitVec::from_bytes(bitmap),
+            number_of_bits,
+            number_of_hash_functions,
+            hash_function,
+        }
+    }
+
+    /// Returns the bit positions of the item using double hashing.
+    fn positions(&self, item: &[u8]) -> impl Iterator<Item = usize> {
+        let (hash_one, hash_two) = self.hash_function.hash(item);
+        let number_of_bits = self.number_of_bits;
+        (0..self.number_of_hash_functions as u64).map(move |i| {
+            let hash = match i {
+                0 => hash_one,
+                1 => hash_two,
+                // Largest u64 prime.
+                _ => hash_one.wrapping_add(i.wrapping_mul(hash_two)) % 0xFFFF_FFFF_FFFF_FFC5,
+            };
+            (hash % number_of_bits) as usize
+        })
+    }
+
+    pub fn check(&self, item: &[u8]) -> bool {
+        self.positions(item)
+            .all(|bit| self.bit_vec.get(bit).unwrap_or(false))
+    }
+
+    pub fn set(&mut self, item: &[u8]) {
+        for bit in self.positions(item) {
+            self.bit_vec.set(bit, true);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bit_vec.clear();
+    }
+
+    pub fn bitmap(&self) -> Vec<u8> {
+        self.bit_vec.to_bytes()
+    }
+
+    pub fn number_of_bits(&self) -> u64 {
+        self.number_of_bits
+    }
+
+    pub fn number_of_set_bits(&self) -> u64 {
+        self.bit_vec.count_ones()
+    }
+
+    pub fn number_of_hash_functions(&self) -> u32 {
+        self.number_of_hash_functions
+    }
+}
+
+/// Number of bits in a block of a `BlockedBloom`. This is the size of a 64 byte cache line.
+pub const BLOCK_BITS: u64 = 512;
+const BLOCK_WORDS: usize = (BLOCK_BITS / 64) as usize;
+
+/// Bloom filter with a blocked (cache line) layout.
+/// An item is hashed to a single block and all of its bits are set within that block, so every
+/// `set` and `check` touches one cache line regardless of the number of hash functions.
+/// Blocks are filled unevenly, so a blocked filter needs a few more bits per item than a standard
+/// filter to stay within the same false positive rate.
+pub struct BlockedBloom {
+    blocks: Vec<u64>,
+    number_of_blocks: u64,
+    number_of_hash_functions: u32,
+    hash_function: BloomHashFunction,
+}
+
+impl BlockedBloom {
+    /// Instantiate an empty BlockedBloom sized for the capacity and false positive rate.
+    pub fn new(capacity: usize, fp_rate: f64, hash_function: BloomHashFunction) -> BlockedBloom {
+        let (bits_per_item, number_of_hash_functions) = Self::optimal_parameters(fp_rate);
+        let number_of_bits = (capacity as f64 * bits_per_item).ceil();
+        let number_of_blocks = ((number_of_bits / BLOCK_BITS as f64).ceil() as u64).max(1);
+        BlockedBloom {
+            blocks: vec![0; number_of_blocks as usize * BLOCK_WORDS],
+            number_of_blocks,
+            number_of_hash_functions,
+            hash_function,
+        }
+    }
+
+    /// Create a BlockedBloom from an existing bitmap (RDB load).
+    pub fn from_existing(
+        bitmap: &[u8],
+        number_of_bits: u64,
+        number_of_hash_functions: u32,
+        hash_function: BloomHashFunction,
+    ) -> BlockedBloom {
+        let number_of_blocks = number_of_bits / BLOCK_BITS;
+        let mut blocks: Vec<u64> = bitmap
+            .chunks(8)
+            .map(|chunk| {
+                let mut word = [0u8; 8];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u64::from_le_bytes(word)
+            })
+            .collect();
+        blocks.resize(number_of_blocks as usize * BLOCK_WORDS, 0);
+        BlockedBloom {
+            blocks,
+            number_of_blocks,
+            number_of_hash_functions,
+            hash_function,
+        }
+    }
+
+    /// Returns the bits per item and number of hash functions needed for a blocked filter to
+    /// stay within the false positive rate, starting from the optimal values of a standard filter.
+    fn optimal_parameters(fp_rate: f64) -> (f64, u32) {
+        let ln2 = std::f64::consts::LN_2;
+        let mut bits_per_item = -fp_rate.ln() / (ln2 * ln2);
+        let mut number_of_hash_functions = 1;
+        for _ in 0..200 {
+            number_of_hash_functions =
+                ((bits_per_item * ln2).round() as u32).clamp(1, BLOCK_BITS as u32);
+            if Self::estimated_fp_rate(bits_per_item, number_of_hash_functions) <= fp_rate {
+                break;
+            }
+            bits_per_item *= 1.05;
+        }
+        (bits_per_item, number_of_hash_functions)
+    }
+
+    /// Estimates the false positive rate of a blocked filter filled to capacity.
+    /// The number of items in a block follows a Poisson distribution, and each block behaves
+    /// like a standard filter of `BLOCK_BITS` bits holding that many items.
+    fn estimated_fp_rate(bits_per_item: f64, number_of_hash_functions: u32) -> f64 {
+        let mean = BLOCK_BITS as f64 / bits_per_item;
+        let max_items = (mean + 10.0 * mean.sqrt() + 10.0).ceil() as u64;
+        let k = number_of_hash_functions as f64;
+        let mut probability = (-mean).exp();
+        let mut fp_rate = 0.0;
+        for items in 0..=max_items {
+            if items > 0 {
+                probability *= mean / items as f64;
+            }
+            let bit_set = 1.0 - (1.0 - 1.0 / BLOCK_BITS as f64).powf(k * items as f64);
+            fp_rate += probability * bit_set.powf(k);
+        }
+        fp_rate
+    }
+
+    /// Hashes the item and returns the index of its block along with the bit positions
+    /// (relative to the block) of the item.
+    fn positions(&self, item: &[u8]) -> (usize, impl Iterator<Item = usize>) {
+        let (hash_one, hash_two) = self.hash_function.hash(item);
+        let block = (hash_one % self.number_of_blocks) as usize * BLOCK_WORDS;
+        // Every bit position is drawn from a splitmix64 sequence seeded by the second hash.
+        let bits = (1..=self.number_of_hash_functions as u64).map(move |i| {
+            let mut z = hash_two.wrapping_add(i.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            ((z ^ (z >> 31)) % BLOCK_BITS) as usize
+        });
+        (block, bits)
+    }
+
+    pub fn check(&self, item: &[u8]) -> bool {
+        let (block, mut bits) = self.positions(item);
+        bits.all(|bit| self.blocks[block + bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    pub fn set(&mut self, item: &[u8]) {
+        let (block, bits) = self.positions(item);
+        for bit in bits {
+            self.blocks[block + bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.fill(0);
+    }
+
+    /// Overwrite the bytes of the bitmap at the offset, in the order of `bitmap`.
+    pub fn load_bitmap_chunk(&mut self, offset: usize, chunk: &[u8]) {
+        for (idx, byte) in chunk.iter().enumerate() {
+            let pos = offset + idx;
+            if let Some(word) = self.blocks.get_mut(pos / 8) {
+                let shift = (pos % 8) * 8;
+                *word = (*word & !(0xFF << shift)) | ((*byte as u64) << shift);
+            }
+        }
+    }
+
+    /// Return the bitmap as bytes, with every 64 bit word of a block in little endian order.
+    pub fn bitmap(&self) -> Vec<u8> {
+        self.blocks
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    pub fn number_of_bits(&self) -> u64 {
+        self.number_of_blocks * BLOCK_BITS
+    }
+
+    pub fn number_of_set_bits(&self) -> u64 {
+        self.blocks
+            .iter()
+            .map(|word| word.count_ones() as u64)
+            .sum()
+    }
+
+    pub fn number_of_hash_functions(&self) -> u32 {
+        self.number_of_hash_functions
+    }
+}
+
+/// Options of RedisBloom objects, as saved in their RDB payload and BF.SCANDUMP header.
+pub const REDISBLOOM_OPT_NOROUND: u64 = 1;
+pub const REDISBLOOM_OPT_FORCE64: u64 = 4;
+pub const REDISBLOOM_OPT_NO_SCALING: u64 = 8;
+
+/// Bloom filter with the layout of the RedisBloom module, so that its bitmap can be exchanged with it.
+/// The bit positions are `(hash_one + i * hash_two) % number_of_bits` for the i-th hash function, and bit x is
+/// the (x % 8)-th least significant bit of byte x / 8. Filters are sized like RedisBloom sizes them, without
+/// rounding the number of bits up to a power of two.
+pub struct CompatBloom {
+    bytes: Vec<u8>,
+    number_of_bits: u64,
+    number_of_hash_functions: u32,
+    hash_function: BloomHashFunction,
+}
+
+impl CompatBloom {
+    /// Instantiate an empty CompatBloom sized for the capacity and false positive rate.
+    pub fn new(capacity: u64, fp_rate: f64, hash_function: BloomHashFunction) -> CompatBloom {
+        let bits_per_item = Self::bits_per_item(fp_rate);
+        let number_of_bits = ((capacity as f64 * bits_per_item) as u64).max(1);
+        let number_of_hash_functions =
+            ((std::f64::consts::LN_2 * bits_per_item).ceil() as u32).max(1);
+        CompatBloom {
+            bytes: vec![0; number_of_bits.div_ceil(8) as usize],
+            number_of_bits,
+            number_of_hash_functions,
+            hash_function,
+        }
+    }
+
+    /// Create a CompatBloom from an existing bitmap (RDB load). RedisBloom may allocate more bytes than the
+    /// number of bits needs, which are kept as is.
+    pub fn from_existing(
+        bitmap: &[u8],
+        number_of_bits: u64,
+        number_of_hash_functions: u32,
+        hash_function: BloomHashFunction,
+    ) -> CompatBloom {
+        let mut bytes = bitmap.to_vec();
+        if (bytes.len() as u64) < number_of_bits.div_ceil(8) {
+            bytes.resize(number_of_bits.div_ceil(8) as usize, 0);
+        }
+        CompatBloom {
+            bytes,
+            number_of_bits,
+            number_of_hash_functions,
+            hash_function,
+        }
+    }
+
+    /// Returns the bits per item of a filter with the false positive rate: -ln(fp_rate) / ln(2)^2.
+    pub fn bits_per_item(fp_rate: f64) -> f64 {
+        let ln2 = std::f64::consts::LN_2;
+        -fp_rate.ln() / (ln2 * ln2)
+    }
+
+    /// Returns the bit positions of an item. Like RedisBloom, the positions are computed in 64 bit arithmetic for
+    /// both hash functions: the hashes of 32 bit MurmurHash2 filters are widened to 64 bits before they are combined.
+    fn positions(&self, item: &[u8]) -> impl Iterator<Item = u64> {
+        let (hash_one, hash_two) = self.hash_function.hash(item);
+        let number_of_bits = self.number_of_bits;
+        (0..self.number_of_hash_functions as u64)
+            .map(move |i| hash_one.wrapping_add(i.wrapping_mul(hash_two)) % number_of_bits)
+    }
+
+    pub fn check(&self, item: &[u8]) -> bool {
+        self.positions(item)
+            .all(|bit| self.bytes[(bit / 8) as usize] & (1 << (bit % 8)) != 0)
+    }
+
+    pub fn set(&mut self, item: &[u8]) {
+        for bit in self.positions(item) {
+            self.bytes[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.fill(0);
+    }
+
+    pub fn bitmap(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+
+    pub fn number_of_bits(&self) -> u64 {
+        self.number_of_bits
+    }
+
+    pub fn number_of_set_bits(&self) -> u64 {
+        self.bytes.iter().map(|byte| byte.count_ones() as u64).sum()
+    }
+
+    pub fn number_of_hash_functions(&self) -> u32 {
+        self.number_of_hash_functions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::{
+        FIXED_SEED, FIXED_SIP_KEY_ONE_A, FIXED_SIP_KEY_ONE_B, FIXED_SIP_KEY_TWO_A,
+        FIXED_SIP_KEY_TWO_B,
+    };
+    use crate::rdb;
+    use crate::rdb::RdbWriter;
+    use rand::{distributions::Alphanumeric, Rng};
+
+    /// Returns random string with specified number of characters.
+    fn random_prefix(len: usize) -> String {
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(len)
+            .map(char::from)
+            .collect()
+    }
+
+    /// Loops until the capacity of the provided bloom filter is reached and adds a new item to it in every iteration.
+    /// The item name is rand_prefix + the index (starting from starting_item_idx).
+    /// With every add operation, fp_count is tracked as we expect the add operation to return 1, since it is a new item.
+    /// Returns the number of errors (false positives) and the final item index.
+    fn add_items_till_capacity(
+        bf: &mut BloomFilterType,
+        capacity_needed: i64,
+        starting_item_idx: i64,
+        rand_prefix: &String,
+    ) -> (i64, i64) {
+        let mut new_item_idx = starting_item_idx;
+        let mut fp_count = 0;
+        let mut cardinality = bf.cardinality();
+        while cardinality < capacity_needed {
+            let item = format!("{}{}", rand_prefix, new_item_idx);
+            let result = bf.add_item(item.as_bytes());
+            match result {
+                Ok(0) => {
+                    fp_count += 1;
+                }
+                Ok(1) => {
+                    cardinality += 1;
+                }
+                Ok(i64::MIN..=-1_i64) | Ok(2_i64..=i64::MAX) => {
+                    panic!("We do not expect add_item to return any Integer other than 0 or 1.")
+                }
+                Err(e) => {
+                    panic!("We do not expect add_item to throw errors on this scalable filter test, {:?}", e);
+                }
+            };
+            new_item_idx += 1;
+        }
+        (fp_count, new_item_idx - 1)
+    }
+
+    /// Loops from the start index till the end index and uses the exists operation on the provided bloom filter.
+    /// The item name used in exists operations is rand_prefix + the index (based on the iteration).
+    /// The results are matched against the `expected_result` and an error_count tracks the wrong results.
+    /// Asserts that the error_count is within the expected false positive (+ margin) rate.
+    /// Returns the error count and number of operations performed.
+    fn check_items_exist(
+        bf: &BloomFilterType,
+        start_idx: i64,
+        end_idx: i64,
+        expected_result: bool,
+        rand_prefix: &String,
+    ) -> (i64, i64) {
+        let mut error_count = 0;
+        for i in start_idx..=end_idx {
+            let item = format!("{}{}", rand_prefix, i);
+            let result = bf.item_exists(item.as_bytes());
+            if result != expected_result {
+                error_count += 1;
+            }
+        }
+        let num_operations = (end_idx - start_idx) + 1;
+        (error_count, num_operations)
+    }
+
+    fn fp_assert(error_count: i64, num_operations: i64, expected_fp_rate: f32, fp_margin: f32) {
+        let real_fp_rate = error_count as f32 / num_operations as f32;
+        let fp_rate_with_margin = expected_fp_rate + fp_margin;
+        assert!(
+            real_fp_rate < fp_rate_with_margin,
+            "The actual fp_rate, {}, is greater than the configured fp_rate with margin. {}.",
+            real_fp_rate,
+            fp_rate_with_margin
+        );
+    }
+
+    fn verify_restored_items(
+        original_bloom_filter_type: &BloomFilterType,
+        restored_bloom_filter_type: &BloomFilterType,
+        add_operation_idx: i64,
+        expected_fp_rate: f32,
+        fp_margin: f32,
+        rand_prefix: &String,
+    ) {
+        assert_eq!(
+            restored_bloom_filter_type.capacity(),
+            original_bloom_filter_type.capacity()
+        );
+        assert_eq!(
+            restored_bloom_filter_type.cardinality(),
+            original_bloom_filter_type.cardinality(),
+        );
+        assert_eq!(
+            restored_bloom_filter_type.free_effort(),
+            original_bloom_filter_type.free_effort()
+        );
+        assert_eq!(
+            restored_bloom_filter_type.memory_usage(),
+            original_bloom_filter_type.memory_usage()
+        );
+        assert!(restored_bloom_filter_type
+            .filters
+            .iter()
+            .all(|restore_filter| original_bloom_filter_type
+                .filters
+                .iter()
+                .any(|filter| filter.bloom.number_of_hash_functions()
+                    == restore_filter.bloom.number_of_hash_functions())));
+        assert!(restored_bloom_filter_type
+            .filters
+            .iter()
+            .all(|restore_filter| original_bloom_filter_type
+                .filters
+                .iter()
+                .any(|filter| filter.bloom.bitmap() == restore_filter.bloom.bitmap())));
+        let (error_count, _) = check_items_exist(
+            restored_bloom_filter_type,
+            1,
+            add_operation_idx,
+            true,
+            rand_prefix,
+        );
+        assert!(error_count == 0);
+        let (error_count, num_operations) = check_items_exist(
+            restored_bloom_filter_type,
+            add_operation_idx + 1,
+            add_operation_idx * 2,
+            false,
+            rand_prefix,
+        );
+        fp_assert(error_count, num_operations, expected_fp_rate, fp_margin);
+    }
+
+    #[test]
+    fn test_non_scaling_filter() {
+        let rand_prefix = random_prefix(7);
+        // 1 in every 1000 operations is expected to be a false positive.
+        let expected_fp_rate: f32 = 0.001;
+        let initial_capacity = 10000;
+        // Expansion of 0 indicates non scaling.
+        let expansion = 0;
+        // Validate the non scaling behavior of the bloom filter.
+        let mut bf = BloomFilterType::new_reserved(
+            expected_fp_rate,
+            initial_capacity,
+            expansion,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        let (error_count, add_operation_idx) =
+            add_items_till_capacity(&mut bf, initial_capacity as i64, 1, &rand_prefix);
+        assert_eq!(
+            bf.add_item(b"new_item"),
+            Err(BloomError::NonScalingFilterFull)
+        );
+        assert_eq!(bf.capacity(), initial_capacity as i64);
+        assert_eq!(bf.cardinality(), initial_capacity as i64);
+        assert_eq!(bf.free_effort(), 1);
+        assert!(bf.memory_usage() > 0);
+        // Use a margin on the expected_fp_rate when asserting for correctness.
+        let fp_margin = 0.002;
+        // Validate that item "add" operations on bloom filters are ensuring correctness.
+        fp_assert(error_count, add_operation_idx, expected_fp_rate, fp_margin);
+        // Validate item "exists" operations on bloom filters are ensuring correctness.
+        // This tests for items already added to the filter and expects them to exist.
+        let (error_count, _) = check_items_exist(&bf, 1, add_operation_idx, true, &rand_prefix);
+        assert!(error_count == 0);
+        // This tests for items which are not added to the filter and expects them to not exist.
+        let (error_count, num_operations) = check_items_exist(
+            &bf,
+            add_operation_idx + 1,
+            add_operation_idx * 2,
+            false,
+            &rand_prefix,
+        );
+        // Validate that the real fp_rate is not much more than the configured fp_rate.
+        fp_assert(error_count, num_operations, expected_fp_rate, fp_margin);
+
+        // Verify restore
+        let mut restore_bf = BloomFilterType::create_copy_from(&bf);
+        assert_eq!(
+            restore_bf.add_item(b"new_item"),
+            Err(BloomError::NonScalingFilterFull)
+        );
+        verify_restored_items(
+            &bf,
+            &restore_bf,
+            add_operation_idx,
+            expected_fp_rate,
+            fp_margin,
+            &rand_prefix,
+        );
+    }
+
+    #[test]
+    fn test_scaling_filter() {
+        let rand_prefix = random_prefix(7);
+        // 1 in every 1000 operations is expected to be a false positive.
+        let expected_fp_rate: f32 = 0.001;
+        let initial_capacity = 10000;
+        let expansion = 2;
+        let num_filters_to_scale = 5;
+        let mut bf = BloomFilterType::new_reserved(
+            expected_fp_rate,
+            initial_capacity,
+            expansion,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        assert_eq!(bf.capacity(), initial_capacity as i64);
+        assert_eq!(bf.cardinality(), 0);
+        let mut total_error_count = 0;
+        let mut add_operation_idx = 0;
+        // Validate the scaling behavior of the bloom filter.
+        for filter_idx in 1..=num_filters_to_scale {
+            let expected_total_capacity = initial_capacity * (expansion.pow(filter_idx) - 1) as u64;
+            let (error_count, new_add_operation_idx) = add_items_till_capacity(
+                &mut bf,
+                expected_total_capacity as i64,
+                add_operation_idx + 1,
+                &rand_prefix,
+            );
+            add_operation_idx = new_add_operation_idx;
+            total_error_count += error_count;
+            assert_eq!(bf.capacity(), expected_total_capacity as i64);
+            assert_eq!(bf.cardinality(), expected_total_capacity as i64);
+            assert_eq!(
+                bf.free_effort(),
+                bf.memory_usage().div_ceil(FREE_EFFORT_BYTES_PER_UNIT)
+            );
+            assert!(bf.memory_usage() > 0);
+        }
+        // Use a margin on the expected_fp_rate when asserting for correctness.
+        let fp_margin = 0.002;
+        // Validate that item "add" operations on bloom filters are ensuring correctness.
+        fp_assert(
+            total_error_count,
+            add_operation_idx,
+            expected_fp_rate,
+            fp_margin,
+        );
+        // Validate item "exists" operations on bloom filters are ensuring correctness.
+        // This tests for items already added to the filter and expects them to exist.
+        let (error_count, _) = check_items_exist(&bf, 1, add_operation_idx, true, &rand_prefix);
+        assert!(error_count == 0);
+        // This tests for items which are not added to the filter and expects them to not exist.
+        let (error_count, num_operations) = check_items_exist(
+            &bf,
+            add_operation_idx + 1,
+            add_operation_idx * 2,
+            false,
+            &rand_prefix,
+        );
+        // Validate that the real fp_rate is not much more than the configured fp_rate.
+        fp_assert(error_count, num_operations, expected_fp_rate, fp_margin);
+        // Validate that the estimated cardinality based on the set bits is close to the number of items added.
+        let estimation_error = (bf.estimated_cardinality() - bf.cardinality()).abs() as f64;
+        assert!(estimation_error / (bf.cardinality() as f64) < 0.05);
+
+        // Verify restore
+        let restore_bloom_filter_type = BloomFilterType::create_copy_from(&bf);
+        verify_restored_items(
+            &bf,
+            &restore_bloom_filter_type,
+            add_operation_idx,
+            expected_fp_rate,
+            fp_margin,
+            &rand_prefix,
+        );
+    }
+
+    #[test]
+    fn test_validate_capacity() {
+        let rand_prefix = random_prefix(7);
+        let mut bf = BloomFilterType::new_reserved(
+            0.01,
+            10,
+            0,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        add_items_till_capacity(&mut bf, 4, 1, &rand_prefix);
+        assert_eq!(bf.validate_capacity_for(6), Ok(()));
+        assert_eq!(
+            bf.validate_capacity_for(7),
+            Err(BloomError::NonScalingFilterFull)
+        );
+        // A scaling filter can hold the items across the filters it scales out to.
+        bf.expansion = 2;
+        assert_eq!(bf.validate_capacity_for(1000), Ok(()));
+        let (_, add_operation_idx) = add_items_till_capacity(&mut bf, 1004, 5, &rand_prefix);
+        assert_eq!(bf.filters.len(), 7);
+        assert_eq!(bf.capacity(), 1270);
+        assert_eq!(bf.validate_capacity_for(266), Ok(()));
+        add_items_till_capacity(&mut bf, 1270, add_operation_idx + 1, &rand_prefix);
+        assert_eq!(bf.filters.len(), 7);
+        // A loaded filter holding more items than its capacity is full.
+        bf.expansion = 0;
+        bf.filters[6].num_items = bf.filters[6].capacity + 1;
+        assert_eq!(
+            bf.validate_capacity_for(1),
+            Err(BloomError::NonScalingFilterFull)
+        );
+    }
+
+    #[test]
+    fn test_large_capacity() {
+        // With a high false positive rate, a filter holding more than u32::MAX items has a small bitmap.
+        let capacity = u32::MAX as u64 + 1000;
+        let mut bf = BloomFilterType::new_reserved(
+            0.99,
+            capacity,
+            0,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        assert_eq!(bf.capacity(), capacity as i64);
+        assert_eq!(bf.add_item(b"item"), Ok(1));
+        assert_eq!(bf.cardinality(), 1);
+        assert_eq!(bf.validate_capacity_for(capacity - 1), Ok(()));
+        assert_eq!(
+            bf.validate_capacity_for(capacity),
+            Err(BloomError::NonScalingFilterFull)
+        );
+        bf.filters[0].num_items = capacity - 1;
+        assert_eq!(bf.cardinality(), capacity as i64 - 1);
+        assert_eq!(bf.add_item(b"last item"), Ok(1));
+        assert_eq!(bf.cardinality(), capacity as i64);
+        assert_eq!(
+            bf.add_item(b"one more item"),
+            Err(BloomError::NonScalingFilterFull)
+        );
+    }
+
+    #[test]
+    fn test_free_effort_and_defrag() {
+        let rand_prefix = random_prefix(7);
+        // Small objects are freed and defragmented right away, even when they have scaled out to many filters.
+        let mut small_bf = BloomFilterType::new_reserved(
+            0.01,
+            10,
+            2,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        assert_eq!(small_bf.free_effort(), 1);
+        assert!(small_bf.should_defrag());
+        add_items_till_capacity(&mut small_bf, 630, 1, &rand_prefix);
+        assert_eq!(small_bf.filters.len(), 6);
+        assert_eq!(small_bf.free_effort(), 1);
+        assert!(small_bf.should_defrag());
+        // The free effort of large objects grows with the size of their bitmaps.
+        let medium_bf = BloomFilterType::new_reserved(
+            0.001,
+            100000,
+            2,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        assert!(medium_bf.memory_usage() > FREE_EFFORT_BYTES_PER_UNIT);
+        assert_eq!(
+            medium_bf.free_effort(),
+            medium_bf
+                .memory_usage()
+                .div_ceil(FREE_EFFORT_BYTES_PER_UNIT)
+        );
+        assert!(medium_bf.free_effort() > 1);
+        assert!(medium_bf.should_defrag());
+        let large_bf = BloomFilterType::new_reserved(
+            0.001,
+            10000000,
+            0,
+            BloomFilterLayout::Blocked,
+            BloomHashFunction::XxHash3,
+        )
+        .unwrap();
+        assert!(large_bf.free_effort() > DEFRAG_MAX_FREE_EFFORT);
+        assert!(large_bf.free_effort() > 50 * medium_bf.free_effort());
+        assert!(!large_bf.should_defrag());
+        // A copy has the same free effort as the original object.
+        let copied_bf = BloomFilterType::create_copy_from(&large_bf);
+        assert_eq!(copied_bf.free_effort(), large_bf.free_effort());
+    }
+
+    #[test]
+    fn test_sip_keys() {
+        // The value of sip keys generated by the sip_keys with fixed seed should be equal to the constant in configs.rs
+        let test_bloom_filter =
+            bloomfilter::Bloom::<[u8]>::new_for_fp_rate_with_seed(1000, 0.5, &FIXED_SEED);
+        let test_sip_keys = test_bloom_filter.sip_keys();
+        assert_eq!(test_sip_keys[0].0, FIXED_SIP_KEY_ONE_A);
+        assert_eq!(test_sip_keys[0].1, FIXED_SIP_KEY_ONE_B);
+        assert_eq!(test_sip_keys[1].0, FIXED_SIP_KEY_TWO_A);
+        assert_eq!(test_sip_keys[1].1, FIXED_SIP_KEY_TWO_B);
+    }
+
+    /// Scales out a filter of the provided layout and hash function and validates its correctness
+    /// along with the correctness of a copy of it.
+    fn validate_scaling_filter(layout: BloomFilterLayout, hash_function: BloomHashFunction) {
+        let rand_prefix = random_prefix(7);
+        let expected_fp_rate: f32 = 0.001;
+        let initial_capacity = 10000;
+        let expansion = 2;
+        let num_filters_to_scale = 3;
+        let mut bf = BloomFilterType::new_reserved(
+            expected_fp_rate,
+            initial_capacity,
+            expansion,
+            layout,
+            hash_function,
+        )
+        .unwrap();
+        assert_eq!(bf.layout, layout);
+        assert_eq!(bf.hash_function, hash_function);
+        let mut total_error_count = 0;
+        let mut add_operation_idx = 0;
+        for filter_idx in 1..=num_filters_to_scale {
+            let expected_total_capacity = initial_capacity * (expansion.pow(filter_idx) - 1) as u64;
+            let (error_count, new_add_operation_idx) = add_items_till_capacity(
+                &mut bf,
+                expected_total_capacity as i64,
+                add_operation_idx + 1,
+                &rand_prefix,
+            );
+            add_operation_idx = new_add_operation_idx;
+            total_error_count += error_count;
+            assert_eq!(bf.capacity(), expected_total_capacity as i64);
+            assert_eq!(bf.filters.len(), filter_idx as usize);
+        }
+        assert!(bf
+            .filters
+            .iter()
+            .all(|filter| filter.bloom.layout() == layout
+                && filter.bloom.hash_function() == hash_function));
+        let fp_margin = 0.002;
+        fp_assert(
+            total_error_count,
+            add_operation_idx,
+            expected_fp_rate,
+            fp_margin,
+        );
+        let (error_count, _) = check_items_exist(&bf, 1, add_operation_idx, true, &rand_prefix);
+        assert!(error_count == 0);
+        let (error_count, num_operations) = check_items_exist(
+            &bf,
+            add_operation_idx + 1,
+            add_operation_idx * 2,
+            false,
+            &rand_prefix,
+        );
+        fp_assert(error_count, num_operations, expected_fp_rate, fp_margin);
+
+        // Verify restore
+        let restore_bloom_filter_type = BloomFilterType::create_copy_from(&bf);
+        assert_eq!(restore_bloom_filter_type.layout, layout);
+        assert_eq!(restore_bloom_filter_type.hash_function, hash_function);
+        verify_restored_items(
+            &bf,
+            &restore_bloom_filter_type,
+            add_operation_idx,
+            expected_fp_rate,
+            fp_margin,
+            &rand_prefix,
+        );
+
+        // Verify clear
+        let number_of_bits = bf.filters[0].bloom.number_of_bits();
+        bf.clear();
+        assert_eq!(bf.filters.len(), 1);
+        assert_eq!(bf.cardinality(), 0);
+        assert_eq!(bf.estimated_cardinality(), 0);
+        assert_eq!(bf.capacity(), initial_capacity as i64);
+        assert_eq!(bf.expansion, expansion);
+        assert_eq!(bf.fp_rate, expected_fp_rate);
+        assert_eq!(bf.filters[0].bloom.number_of_bits(), number_of_bits);
+        assert!(bf.filters[0].bloom.bitmap().iter().all(|byte| *byte == 0));
+        let (error_count, _) = check_items_exist(&bf, 1, add_operation_idx, false, &rand_prefix);
+        assert!(error_count == 0);
+        let (error_count, add_operation_idx) =
+            add_items_till_capacity(&mut bf, initial_capacity as i64, 1, &rand_prefix);
+        fp_assert(error_count, add_operation_idx, expected_fp_rate, fp_margin);
+        assert_eq!(bf.filters.len(), 1);
+    }
+
+    #[test]
+    fn test_blocked_filter() {
+        validate_scaling_filter(BloomFilterLayout::Blocked, BloomHashFunction::SipHash);
+        let bf = BloomFilter::new(
+            0.001,
+            10000,
+            BloomFilterLayout::Blocked,
+            BloomHashFunction::SipHash,
+        );
+        assert_eq!(bf.bloom.number_of_bits() % BLOCK_BITS, 0);
+    }
+
+    #[test]
+    fn test_hash_functions() {
+        for hash_function in [BloomHashFunction::XxHash3, BloomHashFunction::MurmurHash3] {
+            validate_scaling_filter(BloomFilterLayout::Standard, hash_function);
+            validate_scaling_filter(BloomFilterLayout::Blocked, hash_function);
+            assert_eq!(
+                BloomHashFunction::from_encoding(hash_function.encoding()),
+                Some(hash_function)
+            );
+            assert_eq!(
+                BloomHashFunction::from_name(&hash_function.as_str().to_uppercase()),
+                Some(hash_function)
+            );
+        }
+        // SipHash on the standard layout uses the same sizing and bit positions as the "bloomfilter" crate.
+        let mut bf = BloomFilter::new(
+            0.01,
+            1000,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        );
+        let mut crate_bf = bloomfilter::Bloom::<[u8]>::new_for_fp_rate_with_seed(
+            1000,
+            0.01_f32 as f64,
+            &FIXED_SEED,
+        );
+        for i in 0..100 {
+            let item = format!("item{}", i);
+            bf.set(item.as_bytes());
+            crate_bf.set(item.as_bytes());
+        }
+        assert_eq!(bf.bloom.number_of_bits(), crate_bf.number_of_bits());
+        assert_eq!(
+            bf.bloom.number_of_hash_functions(),
+            crate_bf.number_of_hash_functions()
+        );
+        assert_eq!(bf.bloom.bitmap(), crate_bf.bitmap());
+    }
+
+    #[test]
+    fn test_redisbloom_filter() {
+        for hash_function in [
+            BloomHashFunction::MurmurHash64A,
+            BloomHashFunction::MurmurHash2,
+        ] {
+            validate_scaling_filter(BloomFilterLayout::RedisBloom, hash_function);
+        }
+        assert_eq!(
+            BloomFilterLayout::from_encoding(BloomFilterLayout::RedisBloom.encoding()),
+            Some(BloomFilterLayout::RedisBloom)
+        );
+        // The hashes are the same as the ones computed by RedisBloom.
+        assert_eq!(
+            BloomHashFunction::MurmurHash64A.hash(b"hello world"),
+            (0xbae8fb35317acde1, 0xa5c3078260d44436)
+        );
+        assert_eq!(
+            BloomHashFunction::MurmurHash2.hash(b"hello world"),
+            (0x48d0c363, 0x76aefdba)
+        );
+        // Filters are sized like RedisBloom sizes them, without rounding up the number of bits.
+        let bf = BloomFilter::new(
+            0.01,
+            1000,
+            BloomFilterLayout::RedisBloom,
+            BloomHashFunction::MurmurHash64A,
+        );
+        assert_eq!(bf.bloom.number_of_bits(), 9585);
+        assert_eq!(bf.bloom.number_of_hash_functions(), 7);
+        assert_eq!(bf.bloom.bitmap().len(), 1199);
+        // Bit x of the bitmap is the (x % 8)-th least significant bit of byte x / 8.
+        let mut bf = BloomFilter::new(
+            0.01,
+            1000,
+            BloomFilterLayout::RedisBloom,
+            BloomHashFunction::MurmurHash64A,
+        );
+        bf.set(b"item");
+        let bitmap = bf.bloom.bitmap();
+        let (hash_one, hash_two) = BloomHashFunction::MurmurHash64A.hash(b"item");
+        for i in 0..7_u64 {
+            let bit = hash_one.wrapping_add(i.wrapping_mul(hash_two)) % 9585;
+            assert_ne!(bitmap[(bit / 8) as usize] & (1 << (bit % 8)), 0);
+        }
+        // The positions are the ones of RedisBloom for 64 bit (FORCE64) and 32 bit filters, whether or not their
+        // number of bits is rounded up to a power of two.
+        for (hash_function, force64) in [
+            (BloomHashFunction::MurmurHash64A, true),
+            (BloomHashFunction::MurmurHash2, false),
+        ] {
+            for (number_of_bits, n2) in [(9585, 0), (1 << 14, 14)] {
+                let bloom = CompatBloom::from_existing(&[], number_of_bits, 7, hash_function);
+                for i in 0..1000 {
+                    let item = format!("item{}", i);
+                    assert_eq!(
+                        bloom.positions(item.as_bytes()).collect::<Vec<u64>>(),
+                        redisbloom_positions(
+                            hash_function.hash(item.as_bytes()),
+                            7,
+                            number_of_bits,
+                            n2,
+                            force64
+                        )
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the bit positions of an item in a RedisBloom filter, ported from `bloom_check_h` and `CHECK_ADD_FUNC`
+    /// of RedisBloom with their C integer types. The hashes of `bloom_hashval` are `uint64_t`, while the index and
+    /// modulus are `uint32_t` in the 32 bit variant, so `a + i * b` is computed in 64 bits by both variants.
+    fn redisbloom_positions(
+        hash: (u64, u64),
+        hashes: u32,
+        bits: u64,
+        n2: u8,
+        force64: bool,
+    ) -> Vec<u64> {
+        let (a, b) = hash;
+        let use_64 = if n2 > 0 {
+            force64 || n2 > 31
+        } else {
+            force64 || bits > u32::MAX as u64
+        };
+        if use_64 {
+            let modulus: u64 = if n2 > 0 { 1 << n2 } else { bits };
+            (0..hashes as u64)
+                .map(|i| a.wrapping_add(i.wrapping_mul(b)) % modulus)
+                .collect()
+        } else {
+            let modulus: u32 = if n2 > 0 { 1 << n2 } else { bits as u32 };
+            (0..hashes)
+                .map(|i: u32| {
+                    let x: u32 =
+                        (a.wrapping_add((i as u64).wrapping_mul(b)) % modulus as u64) as u32;
+                    x as u64
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_scandump_and_loadchunk() {
+        let rand_prefix = random_prefix(7);
+        for (layout, hash_function) in [
+            (BloomFilterLayout::Standard, BloomHashFunction::SipHash),
+            (BloomFilterLayout::Standard, BloomHashFunction::MurmurHash3),
+            (BloomFilterLayout::Blocked, BloomHashFunction::XxHash3),
+            (
+                BloomFilterLayout::RedisBloom,
+                BloomHashFunction::MurmurHash64A,
+            ),
+            (
+                BloomFilterLayout::RedisBloom,
+                BloomHashFunction::MurmurHash2,
+            ),
+        ] {
+            let mut bf =
+                BloomFilterType::new_reserved(0.01, 1000, 2, layout, hash_function).unwrap();
+            let (_, add_operation_idx) = add_items_till_capacity(&mut bf, 2500, 1, &rand_prefix);
+            let mut load_chunks = bf.load_chunks();
+            let (iterator, header) = load_chunks.next().unwrap();
+            assert_eq!(iterator, 1);
+            assert_eq!(header, bf.encoded_header());
+            assert_eq!(
+                header.len(),
+                SCANDUMP_HEADER_SIZE + bf.filters.len() * SCANDUMP_FILTER_SIZE
+            );
+            let mut restored_bf = BloomFilterType::from_encoded_header(&header, true).unwrap();
+            assert_eq!(restored_bf.encoded_header(), header);
+            for (next_iterator, chunk) in load_chunks {
+                assert!(restored_bf
+                    .load_encoded_chunk(next_iterator, &chunk)
+                    .is_ok());
+            }
+            assert_eq!(restored_bf.layout, layout);
+            assert_eq!(restored_bf.hash_function, hash_function);
+            assert_eq!(restored_bf.cardinality(), bf.cardinality());
+            for (filter, restored_filter) in bf.filters.iter().zip(&restored_bf.filters) {
+                assert_eq!(filter.bloom.bitmap(), restored_filter.bloom.bitmap());
+            }
+            for i in 1..add_operation_idx {
+                let item = format!("{}{}", rand_prefix, i);
+                assert!(restored_bf.item_exists(item.as_bytes()));
+            }
+            // Bitmaps are patched in place by chunks of any size and offset, so that loading a bitmap chunk by chunk
+            // is linear in its size: the words of the bitmap are never copied or reallocated.
+            let words_ptr = |filter: &BloomFilter| match filter.bloom.words() {
+                BitmapWords::Bytes(words) => words.as_ptr() as usize,
+                BitmapWords::U32(words) => words.as_ptr() as usize,
+                BitmapWords::U64(words) => words.as_ptr() as usize,
+            };
+            let mut patched_bf = BloomFilterType::from_encoded_header(&header, true).unwrap();
+            for (filter, patched_filter) in bf.filters.iter().zip(patched_bf.filters.iter_mut()) {
+                let bitmap = filter.bloom.bitmap();
+                let patched_words_ptr = words_ptr(patched_filter);
+                for (idx, chunk) in bitmap.chunks(7).enumerate() {
+                    patched_filter.load_bitmap_chunk(idx * 7, chunk);
+                    assert_eq!(words_ptr(patched_filter), patched_words_ptr);
+                }
+                assert_eq!(patched_filter.bloom.bitmap(), bitmap);
+                patched_filter.load_bitmap_chunk(3, &[0; 2]);
+                assert_eq!(patched_filter.bloom.bitmap()[3..5], [0, 0]);
+                assert_eq!(patched_filter.bloom.bitmap()[5..], bitmap[5..]);
+            }
+            // Chunks past the bitmaps, or which do not fit in their filter, are rejected.
+            let iterator = header.len() as u64 * 1000000;
+            assert_eq!(
+                restored_bf.load_encoded_chunk(iterator, &[0; 8]),
+                Err(BloomError::InvalidChunkOffset)
+            );
+            let first_filter_len = bf.filters[0].bloom.bitmap_len() as u64;
+            assert_eq!(
+                restored_bf.load_encoded_chunk(first_filter_len + 2, &[0; 2]),
+                Err(BloomError::ChunkTooBig)
+            );
+            assert_eq!(
+                BloomFilterType::from_encoded_header(&header[1..], true).err(),
+                Some(BloomError::BadScanDumpData)
+            );
+        }
+        // Headers whose filters exceed the memory limit of bloom objects, alone or together, are rejected before
+        // any bitmap is allocated.
+        let encoded_header = |filter_sizes: &[(u64, u64)]| {
+            let mut header = Vec::new();
+            header.extend_from_slice(&0_u64.to_le_bytes());
+            header.extend_from_slice(&(filter_sizes.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(REDISBLOOM_OPT_FORCE64 as u32).to_le_bytes());
+            header.extend_from_slice(&2_u32.to_le_bytes());
+            for (number_of_bytes, number_of_bits) in filter_sizes {
+                header.extend_from_slice(&number_of_bytes.to_le_bytes());
+                header.extend_from_slice(&number_of_bits.to_le_bytes());
+                header.extend_from_slice(&0_u64.to_le_bytes());
+                header.extend_from_slice(&0.01_f64.to_le_bytes());
+                header.extend_from_slice(&9.585_f64.to_le_bytes());
+                header.extend_from_slice(&7_u32.to_le_bytes());
+                header.extend_from_slice(&1000_u64.to_le_bytes());
+                header.push(0);
+            }
+            header
+        };
+        assert!(
+            BloomFilterType::from_encoded_header(&encoded_header(&[(1199, 9585)]), true).is_ok()
+        );
+        let large_filter = (100 * 1024 * 1024, 800 * 1024 * 1024);
+        for filter_sizes in [
+            vec![(1 << 50, 1 << 53)],
+            vec![large_filter, large_filter],
+            vec![(u64::MAX, 1 << 53)],
+        ] {
+            assert_eq!(
+                BloomFilterType::from_encoded_header(&encoded_header(&filter_sizes), true).err(),
+                Some(BloomError::ExceedsMaxBloomSize)
+            );
+        }
+    }
+
+    #[test]
+    fn test_dump_payload() {
+        let rand_prefix = random_prefix(7);
+        for (layout, hash_function) in [
+            (BloomFilterLayout::Standard, BloomHashFunction::SipHash),
+            (BloomFilterLayout::Blocked, BloomHashFunction::MurmurHash3),
+            (
+                BloomFilterLayout::RedisBloom,
+                BloomHashFunction::MurmurHash64A,
+            ),
+        ] {
+            let mut bf =
+                BloomFilterType::new_reserved(0.01, 1000, 2, layout, hash_function).unwrap();
+            let (_, add_operation_idx) = add_items_till_capacity(&mut bf, 2500, 1, &rand_prefix);
+            let payload = rdb::dump_payload(&bf);
+            // The payload holds a module data type and ends with the RDB version and checksum.
+            assert_eq!(payload[0], rdb::RDB_TYPE_MODULE_2);
+            let footer = &payload[payload.len() - 10..];
+            assert_eq!(footer[..2], rdb::DUMP_RDB_VERSION.to_le_bytes());
+            let restored_bf = rdb::restore_payload(&payload).unwrap();
+            assert_eq!(restored_bf.layout, layout);
+            assert_eq!(restored_bf.hash_function, hash_function);
+            assert_eq!(restored_bf.expansion, bf.expansion);
+            assert_eq!(restored_bf.fp_rate, bf.fp_rate);
+            assert_eq!(restored_bf.cardinality(), bf.cardinality());
+            assert_eq!(restored_bf.filters.len(), bf.filters.len());
+            for i in 1..add_operation_idx {
+                let item = format!("{}{}", rand_prefix, i);
+                assert!(restored_bf.item_exists(item.as_bytes()));
+            }
+            assert_eq!(rdb::dump_payload(&restored_bf), payload);
+            // Corrupted or truncated payloads are rejected.
+            let mut corrupted = payload.clone();
+            corrupted[payload.len() / 2] ^= 1;
+            assert_eq!(
+                rdb::restore_payload(&corrupted).err(),
+                Some(rdb::RdbLoadError::ChecksumMismatch)
+            );
+            assert!(rdb::restore_payload(&payload[..payload.len() - 1]).is_err());
+        }
+        // Objects of the redisbloom layout are also restored from payloads of the RedisBloom data type.
+        let mut bf = BloomFilterType::new_reserved(
+            0.01,
+            100,
+            2,
+            BloomFilterLayout::RedisBloom,
+            BloomHashFunction::MurmurHash2,
+        )
+        .unwrap();
+        let (_, add_operation_idx) = add_items_till_capacity(&mut bf, 250, 1, &rand_prefix);
+        let mut writer = rdb::DumpPayloadWriter::new();
+        writer.save_length(rdb::module_type_id(
+            rdb::REDISBLOOM_FILTER_TYPE_NAME,
+            rdb::REDISBLOOM_FILTER_TYPE_ENCODING_VERSION,
+        ));
+        rdb::save_redisbloom_object(&mut writer, &bf);
+        writer.save_length(0);
+        let redisbloom_value = writer.into_inner();
+        let mut reader = rdb::DumpPayloadReader::new(&redisbloom_value);
+        let (name, encver) = rdb::module_type_name(reader.load_length().unwrap());
+        assert_eq!(name, rdb::REDISBLOOM_FILTER_TYPE_NAME);
+        let restored_bf = rdb::load_redisbloom_object(&mut reader, encver).unwrap();
+        assert_eq!(restored_bf.hash_function, BloomHashFunction::MurmurHash2);
+        assert_eq!(restored_bf.cardinality(), bf.cardinality());
+        for i in 1..add_operation_idx {
+            let item = format!("{}{}", rand_prefix, i);
+            assert!(restored_bf.item_exists(item.as_bytes()));
+        }
+        // Filters whose bitmap does not hold their number of bits, in whole blocks for the blocked layout, are rejected.
+        for (layout, bitmap_len, number_of_bits) in [
+            (BloomFilterLayout::Blocked, 32, 256),
+            (BloomFilterLayout::Blocked, 0, 0),
+            (BloomFilterLayout::Blocked, 72, 576),
+            (BloomFilterLayout::Standard, 10, 160),
+            (BloomFilterLayout::Standard, 0, 0),
+        ] {
+            let mut writer = rdb::DumpPayloadWriter::new();
+            writer.save_unsigned(1);
+            writer.save_unsigned(2);
+            writer.save_float(0.01);
+            writer.save_unsigned(layout.encoding());
+            writer.save_unsigned(BloomHashFunction::XxHash3.encoding());
+            writer.save_string_buffer(&vec![0; bitmap_len]);
+            writer.save_unsigned(number_of_bits);
+            writer.save_unsigned(7);
+            writer.save_unsigned(100);
+            writer.save_unsigned(0);
+            let value = writer.into_inner();
+            let mut reader = rdb::DumpPayloadReader::new(&value);
+            assert_eq!(
+                rdb::load_bloom_object(&mut reader, rdb::BLOOM_FILTER_TYPE_ENCODING_VERSION).err(),
+                Some(rdb::RdbLoadError::InvalidBitmap {
+                    type_name: rdb::BLOOM_FILTER_TYPE_NAME,
+                    bitmap_len,
+                    number_of_bits,
+                })
+            );
+        }
+        // Objects without any filter are rejected.
+        let mut writer = rdb::DumpPayloadWriter::new();
+        writer.save_unsigned(0);
+        let value = writer.into_inner();
+        let mut reader = rdb::DumpPayloadReader::new(&value);
+        assert_eq!(
+            rdb::load_bloom_object(&mut reader, rdb::BLOOM_FILTER_TYPE_ENCODING_VERSION).err(),
+            Some(rdb::RdbLoadError::InvalidNumberOfFilters {
+                type_name: rdb::BLOOM_FILTER_TYPE_NAME,
+                num_filters: 0,
+                max_filters: configs::BLOOM_MAX_FILTERS_PER_OBJ_MAX,
+            })
+        );
+        // Strings saved by the server may be integer encoded or LZF compressed.
+        let encoded_strings = [0xc0, 0xfb, 0xc3, 5, 11, 0, b'a', 0xe0, 1, 0];
+        let mut reader = rdb::DumpPayloadReader::new(&encoded_strings);
+        assert_eq!(reader.load_string(), Some(b"-5".to_vec()));
+        assert_eq!(reader.load_string(), Some(vec![b'a'; 11]));
+        // Compressed strings whose length cannot be reached from their compressed bytes are rejected.
+        let huge_string = [0xc3, 5, 0x81, 0, 0, 1, 0, 0, 0, 0, 0, 0, b'a', 0xe0, 1, 0];
+        let mut reader = rdb::DumpPayloadReader::new(&huge_string);
+        assert_eq!(reader.load_string(), None);
+        // The longest back reference expands 3 bytes into 264 bytes.
+        let expanded_string = [0xc3, 5, 0x41, 0x09, 0, b'a', 0xe0, 0xff, 0];
+        let mut reader = rdb::DumpPayloadReader::new(&expanded_string);
+        assert_eq!(reader.load_string(), Some(vec![b'a'; 265]));
+    }
+
+    #[test]
+    fn test_intersection_cardinality() {
+        let rand_prefix = random_prefix(7);
+        for (layout, hash_function) in [
+            (BloomFilterLayout::Standard, BloomHashFunction::SipHash),
+            (BloomFilterLayout::Standard, BloomHashFunction::XxHash3),
+            (BloomFilterLayout::Blocked, BloomHashFunction::MurmurHash3),
+        ] {
+            let mut bf_one =
+                BloomFilterType::new_reserved(0.001, 10000, 2, layout, hash_function).unwrap();
+            let mut bf_two =
+                BloomFilterType::new_reserved(0.001, 10000, 2, layout, hash_function).unwrap();
+            // Items 4000 to 7999 are added to both objects.
+            for i in 0..8000 {
+                let _ = bf_one.add_item(format!("{}{}", rand_prefix, i).as_bytes());
+                let _ = bf_two.add_item(format!("{}{}", rand_prefix, i + 4000).as_bytes());
+            }
+            let estimate = bf_one.estimated_intersection_cardinality(&bf_two).unwrap();
+            assert!(
+                (estimate - 4000).abs() < 200,
+                "Estimate {} is off",
+                estimate
+            );
+            assert_eq!(
+                bf_two.estimated_intersection_cardinality(&bf_one),
+                Ok(estimate)
+            );
+            // An object intersected with itself is estimated to hold all of its items.
+            let estimate = bf_one.estimated_intersection_cardinality(&bf_one).unwrap();
+            assert!(
+                (estimate - 8000).abs() < 400,
+                "Estimate {} is off",
+                estimate
+            );
+            bf_two.clear();
+            assert_eq!(bf_one.estimated_intersection_cardinality(&bf_two), Ok(0));
+        }
+        // Objects of a different layout, hash function or without any sub filters of the same size cannot be intersected.
+        let bf = BloomFilterType::new_reserved(
+            0.01,
+            1000,
+            2,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        for incompatible_bf in [
+            BloomFilterType::new_reserved(
+                0.01,
+                1000,
+                2,
+                BloomFilterLayout::Blocked,
+                BloomHashFunction::SipHash,
+            )
+            .unwrap(),
+            BloomFilterType::new_reserved(
+                0.01,
+                1000,
+                2,
+                BloomFilterLayout::Standard,
+                BloomHashFunction::XxHash3,
+            )
+            .unwrap(),
+            BloomFilterType::new_reserved(
+                0.01,
+                2000,
+                2,
+                BloomFilterLayout::Standard,
+                BloomHashFunction::SipHash,
+            )
+            .unwrap(),
+        ] {
+            assert_eq!(
+                bf.estimated_intersection_cardinality(&incompatible_bf),
+                Err(BloomError::IncompatibleFilters)
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        for layout in [
+            BloomFilterLayout::Standard,
+            BloomFilterLayout::Blocked,
+            BloomFilterLayout::RedisBloom,
+        ] {
+            // Objects whose filter would exceed the memory limit are rejected before the bitmap is allocated.
+            assert!(matches!(
+                BloomFilterType::new_reserved(
+                    0.01,
+                    configs::BLOOM_CAPACITY_MAX,
+                    2,
+                    layout,
+                    BloomHashFunction::SipHash
+                ),
+                Err(BloomError::ExceedsMaxBloomSize)
+            ));
+            // The size computed before creating a filter is the size it is allocated with.
+            let filter = BloomFilter::new(0.001, 10000, layout, BloomHashFunction::XxHash3);
+            assert_eq!(
+                BloomFilter::compute_size(0.001, 10000, layout),
+                (std::mem::size_of::<BloomFilter>() + filter.bloom.bitmap_len()) as u64
+            );
+        }
+        // Scaling out stops at the memory limit, both when validating a batch of items and when adding an item.
+        let bf = BloomFilterType::new_reserved(
+            0.01,
+            1000,
+            10,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        assert_eq!(
+            bf.validate_capacity_for(u64::MAX),
+            Err(BloomError::ExceedsMaxBloomSize)
+        );
+        let full_filter = BloomFilter::from_existing(
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+            &[0; 8],
+            64,
+            1,
+            1 << 40,
+            1 << 40,
+        );
+        let mut bf = BloomFilterType::from_existing(
+            10,
+            0.01,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+            vec![full_filter],
+        );
+        assert_eq!(bf.add_item(b"item"), Err(BloomError::ExceedsMaxBloomSize));
+        assert_eq!(bf.filters.len(), 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let rand_prefix = random_prefix(7);
+        for (layout, hash_function) in [
+            (BloomFilterLayout::Standard, BloomHashFunction::SipHash),
+            (BloomFilterLayout::Standard, BloomHashFunction::XxHash3),
+            (BloomFilterLayout::Blocked, BloomHashFunction::XxHash3),
+            (
+                BloomFilterLayout::RedisBloom,
+                BloomHashFunction::MurmurHash64A,
+            ),
+        ] {
+            let mut bf_one =
+                BloomFilterType::new_reserved(0.001, 1000, 2, layout, hash_function).unwrap();
+            let mut bf_two =
+                BloomFilterType::new_reserved(0.001, 1000, 2, layout, hash_function).unwrap();
+            // Items 500 to 999 are added to both objects, and the second object scales out into 3 filters.
+            for i in 0..1000 {
+                let _ = bf_one.add_item(format!("{}{}", rand_prefix, i).as_bytes());
+            }
+            for i in 500..3000 {
+                let _ = bf_two.add_item(format!("{}{}", rand_prefix, i).as_bytes());
+            }
+            assert_eq!(bf_one.filters.len(), 1);
+            assert_eq!(bf_two.filters.len(), 2);
+            // The bits set in both bitmaps are counted over the words they are stored in, like over their bytes.
+            let (bitmap_one, bitmap_two) = (
+                bf_one.filters[0].bloom.bitmap(),
+                bf_two.filters[0].bloom.bitmap(),
+            );
+            assert_eq!(
+                bf_one.filters[0]
+                    .bloom
+                    .words()
+                    .number_of_common_set_bits(&bf_two.filters[0].bloom.words()),
+                bitmap_one
+                    .iter()
+                    .zip(bitmap_two.iter())
+                    .map(|(byte_one, byte_two)| (byte_one & byte_two).count_ones() as u64)
+                    .sum::<u64>()
+            );
+            bf_one.merge_from(&bf_two).unwrap();
+            assert_eq!(bf_one.filters.len(), 2);
+            for i in 0..3000 {
+                assert!(bf_one.item_exists(format!("{}{}", rand_prefix, i).as_bytes()));
+            }
+            // The number of items of the merged first filter, which holds 1500 items, is bounded by its capacity.
+            assert_eq!(bf_one.filters[0].num_items, 1000);
+            assert_eq!(bf_one.filters[1].num_items, bf_two.filters[1].num_items);
+            // Merging an object into itself leaves its bitmaps unchanged.
+            let bf_copy = BloomFilterType::create_copy_from(&bf_one);
+            bf_one.merge_from(&bf_copy).unwrap();
+            for (filter, filter_copy) in bf_one.filters.iter().zip(bf_copy.filters.iter()) {
+                assert_eq!(filter.bloom.bitmap(), filter_copy.bloom.bitmap());
+            }
+        }
+        // Objects of a different layout, hash function or filter sizes cannot be merged, and are left unchanged.
+        let mut bf = BloomFilterType::new_reserved(
+            0.01,
+            1000,
+            2,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        bf.add_item(b"item").unwrap();
+        for incompatible_bf in [
+            BloomFilterType::new_reserved(
+                0.01,
+                1000,
+                2,
+                BloomFilterLayout::Blocked,
+                BloomHashFunction::SipHash,
+            )
+            .unwrap(),
+            BloomFilterType::new_reserved(
+                0.01,
+                1000,
+                2,
+                BloomFilterLayout::Standard,
+                BloomHashFunction::XxHash3,
+            )
+            .unwrap(),
+            BloomFilterType::new_reserved(
+                0.01,
+                2000,
+                2,
+                BloomFilterLayout::Standard,
+                BloomHashFunction::SipHash,
+            )
+            .unwrap(),
+        ] {
+            assert_eq!(
+                bf.merge_from(&incompatible_bf),
+                Err(BloomError::IncompatibleFilters)
+            );
+            assert_eq!(bf.cardinality(), 1);
+        }
+        // An object which scaled out cannot be merged into one whose last filter would not be full once merged.
+        let mut scaled_bf = BloomFilterType::new_reserved(
+            0.01,
+            1000,
+            2,
+            BloomFilterLayout::Standard,
+            BloomHashFunction::SipHash,
+        )
+        .unwrap();
+        let mut i = 0;
+        while scaled_bf.filters.len() < 2 {
+            scaled_bf.add_item(format!("item{}", i).as_bytes()).unwrap();
+            i += 1;
+        }
+        scaled_bf.filters[0].num_items = 10;
+        assert_eq!(
+            bf.merge_from(&scaled_bf),
+            Err(BloomError::IncompatibleFilters)
+        );
+        assert_eq!(bf.filters.len(), 1);
+        assert_eq!(bf.cardinality(), 1);
+        // Once the last filter of the object is full, the filters the other object scaled out into are copied.
+        bf.filters[0].num_items = bf.filters[0].capacity;
+        bf.merge_from(&scaled_bf).unwrap();
+        assert_eq!(bf.filters.len(), 2);
+        // The copied filters must fit within the memory limit. The filters report the number of bits they were
+        // loaded with, so objects at the limit are built without allocating their bitmaps.
+        let filter = |number_of_bits: u64, num_items: u64| {
+            BloomFilter::from_existing(
+                BloomFilterLayout::Standard,
+                BloomHashFunction::SipHash,
+                &[0; 8],
+                number_of_bits,
+                1,
+                num_items,
+                1000,
+            )
+        };
+        let object = |filters: Vec<BloomFilter>| {
+            BloomFilterType::from_existing(
+                2,
+                0.01,
+                BloomFilterLayout::Standard,
+                BloomHashFunction::SipHash,
+                filters,
+            )
+        };
+        let copied_bytes = filter(64, 0).number_of_bytes() as u64;
+        for (room, expected_result) in [
+            (copied_bytes, Ok(())),
+            (copied_bytes - 1, Err(BloomError::ExceedsMaxBloomSize)),
+        ] {
+            let number_of_bits = (configs::memory_limit_per_object()
+                - room
+                - std::mem::size_of::<BloomFilterType>() as u64
+                - std::mem::size_of::<BloomFilter>() as u64)
+                * 8;
+            let mut bf = object(vec![filter(number_of_bits, 1000)]);
+            let scaled_bf = object(vec![filter(number_of_bits, 1000), filter(64, 1)]);
+            assert_eq!(bf.merge_from(&scaled_bf), expected_result);
+            assert_eq!(
+                bf.filters.len(),
+                if expected_result.is_ok() { 2 } else { 1 }
+            );
+            assert!(bf.memory_usage() as u64 <= configs::memory_limit_per_object());
+        }
+    }
+}
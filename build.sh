@@ -9,14 +9,14 @@ SCRIPT_DIR=$(pwd)
 echo "Script Directory: $SCRIPT_DIR"
 
 echo "Running cargo and clippy format checks..."
-cargo fmt --check
-cargo clippy --profile release --all-targets -- -D clippy::all
+cargo fmt --all --check
+cargo clippy --workspace --profile release --all-targets -- -D clippy::all
 
 echo "Running cargo build release..."
 cargo build --all --all-targets  --release
 
 echo "Running unit tests..."
-cargo test --features enable-system-alloc
+cargo test --workspace --features valkey-bloom/enable-system-alloc
 
 # Ensure SERVER_VERSION environment variable is set
 if [ -z "$SERVER_VERSION" ]; then
@@ -1,14 +1,30 @@
-use crate::bloom::data_type::BLOOM_FILTER_TYPE;
+use crate::bloom::data_type::{BLOOM_FILTER_TYPE, REDISBLOOM_FILTER_TYPE};
 use crate::bloom::utils;
-use crate::bloom::utils::BloomFilterType;
+use crate::bloom::utils::{BloomError, BloomFilterLayout, BloomFilterType, BloomHashFunction};
 use crate::configs;
 use crate::configs::{
     BLOOM_CAPACITY_MAX, BLOOM_CAPACITY_MIN, BLOOM_EXPANSION_MAX, BLOOM_EXPANSION_MIN,
     BLOOM_FP_RATE_MAX, BLOOM_FP_RATE_MIN,
 };
+use std::collections::HashSet;
 use std::sync::atomic::Ordering;
+use valkey_module::key::{ValkeyKey, ValkeyKeyWritable};
 use valkey_module::NotifyEvent;
-use valkey_module::{Context, ValkeyError, ValkeyResult, ValkeyString, ValkeyValue, VALKEY_OK};
+use valkey_module::{
+    raw, Context, ContextFlags, ValkeyError, ValkeyResult, ValkeyString, ValkeyValue, VALKEY_OK,
+};
+
+/// How BF.MADD and BF.INSERT handle an item which cannot be added.
+#[derive(Clone, Copy, PartialEq)]
+enum BloomAddMode {
+    /// Stop at the first error, which is the last entry of the reply. Items before it remain added.
+    StopOnError,
+    /// Check that every item can be added before adding any of them. Otherwise, reply with the error
+    /// without adding any item.
+    Atomic,
+    /// Reply with an error for every item which cannot be added, and keep adding the items after it.
+    Continue,
+}
 
 fn handle_bloom_add(
     args: &[ValkeyString],
@@ -16,10 +32,23 @@ fn handle_bloom_add(
     item_idx: usize,
     bf: &mut BloomFilterType,
     multi: bool,
+    mode: BloomAddMode,
     add_succeeded: &mut bool,
 ) -> Result<ValkeyValue, ValkeyError> {
     match multi {
         true => {
+            if mode == BloomAddMode::Atomic {
+                // Items which exist already (or repeat within the batch) do not need to be added.
+                let mut new_items = HashSet::new();
+                for item in args.iter().take(argc).skip(item_idx) {
+                    if !bf.item_exists(item.as_slice()) {
+                        new_items.insert(item.as_slice());
+                    }
+                }
+                if let Err(err) = bf.validate_capacity_for(new_items.len() as u64) {
+                    return Err(err.into());
+                }
+            }
             let mut result = Vec::new();
             for item in args.iter().take(argc).skip(item_idx) {
                 match bf.add_item(item.as_slice()) {
@@ -31,7 +60,9 @@ fn handle_bloom_add(
                     }
                     Err(err) => {
                         result.push(ValkeyValue::StaticError(err.as_str()));
-                        break;
+                        if mode != BloomAddMode::Continue {
+                            break;
+                        }
                     }
                 };
             }
@@ -44,7 +75,7 @@ fn handle_bloom_add(
                     *add_succeeded = add_result == 1;
                     Ok(ValkeyValue::Integer(add_result))
                 }
-                Err(err) => Err(ValkeyError::Str(err.as_str())),
+                Err(err) => Err(err.into()),
             }
         }
     }
@@ -73,19 +104,247 @@ pub fn bloom_filter_add_value(
     multi: bool,
 ) -> ValkeyResult {
     let argc = input_args.len();
-    if (!multi && argc != 3) || argc < 3 {
+    if argc < 3 {
         return Err(ValkeyError::WrongArity);
     }
     let mut curr_cmd_idx = 1;
     // Parse the filter name
     let filter_name = &input_args[curr_cmd_idx];
     curr_cmd_idx += 1;
+    let mut add_options = BloomAddOptions::from_configs();
+    if !multi {
+        // BF.ADD <key> [<creation options>] <item>: every argument before the item is a creation option.
+        let item_idx = argc - 1;
+        while curr_cmd_idx < item_idx {
+            curr_cmd_idx =
+                parse_create_option(input_args, curr_cmd_idx, item_idx, &mut add_options)?;
+            curr_cmd_idx += 1;
+        }
+    } else if has_add_options(input_args, curr_cmd_idx) {
+        // BF.MADD <key> [<creation options> ITEMS] <item> [<item> ...]: creation options must be followed by ITEMS.
+        // Otherwise, every argument after the key is an item, even if it is named like an option.
+        curr_cmd_idx = parse_add_options(input_args, curr_cmd_idx, &mut add_options)?;
+    }
+    add_items_creating_filter(
+        ctx,
+        input_args,
+        filter_name,
+        curr_cmd_idx,
+        multi,
+        &add_options,
+    )
+}
+
+/// Options of BF.ADD, BF.MADD and BF.INSERT. The properties of the bloom object created when the key does
+/// not exist default to the module configs, and are ignored if the bloom object already exists.
+struct BloomAddOptions {
+    fp_rate: f32,
+    capacity: u64,
+    expansion: u32,
+    hash_function: BloomHashFunction,
+    nocreate: bool,
+    mode: BloomAddMode,
+}
+
+impl BloomAddOptions {
+    fn from_configs() -> BloomAddOptions {
+        BloomAddOptions {
+            fp_rate: configs::BLOOM_FP_RATE_DEFAULT,
+            capacity: configs::BLOOM_CAPACITY.load(Ordering::Relaxed) as u64,
+            expansion: configs::BLOOM_EXPANSION.load(Ordering::Relaxed) as u32,
+            hash_function: configs::default_hash_function(),
+            nocreate: false,
+            mode: BloomAddMode::StopOnError,
+        }
+    }
+}
+
+/// Check whether the arguments of BF.MADD starting at `idx` are options: the first one is the name of an option
+/// and an ITEMS argument follows it.
+fn has_add_options(input_args: &[ValkeyString], idx: usize) -> bool {
+    let is_option = matches!(
+        input_args[idx].to_string_lossy().to_uppercase().as_str(),
+        "ERROR"
+            | "CAPACITY"
+            | "NOCREATE"
+            | "NONSCALING"
+            | "EXPANSION"
+            | "HASH"
+            | "ATOMIC"
+            | "CONTINUE"
+    );
+    is_option
+        && input_args[idx + 1..]
+            .iter()
+            .any(|arg| arg.to_string_lossy().eq_ignore_ascii_case("ITEMS"))
+}
+
+/// Parse the creation option at `idx` (and its value, which must be before `end_idx`).
+/// Returns the index of the last argument parsed.
+fn parse_create_option(
+    input_args: &[ValkeyString],
+    mut idx: usize,
+    end_idx: usize,
+    options: &mut BloomAddOptions,
+) -> Result<usize, ValkeyError> {
+    match input_args[idx].to_string_lossy().to_uppercase().as_str() {
+        "ERROR" => {
+            if idx >= (end_idx - 1) {
+                return Err(ValkeyError::WrongArity);
+            }
+            idx += 1;
+            options.fp_rate = match input_args[idx].to_string_lossy().parse::<f32>() {
+                Ok(num) if num > BLOOM_FP_RATE_MIN && num < BLOOM_FP_RATE_MAX => num,
+                Ok(num) if !(num > BLOOM_FP_RATE_MIN && num < BLOOM_FP_RATE_MAX) => {
+                    return Err(BloomError::ErrorRateRange.into());
+                }
+                _ => {
+                    return Err(BloomError::BadErrorRate.into());
+                }
+            };
+        }
+        "CAPACITY" => {
+            if idx >= (end_idx - 1) {
+                return Err(ValkeyError::WrongArity);
+            }
+            idx += 1;
+            options.capacity = match input_args[idx].to_string_lossy().parse::<u64>() {
+                Ok(num) if (BLOOM_CAPACITY_MIN..=BLOOM_CAPACITY_MAX).contains(&num) => num,
+                Ok(0) => {
+                    return Err(BloomError::CapacityLargerThan0.into());
+                }
+                _ => {
+                    return Err(BloomError::BadCapacity.into());
+                }
+            };
+        }
+        "NOCREATE" => {
+            options.nocreate = true;
+        }
+        "NONSCALING" => {
+            options.expansion = 0;
+        }
+        "EXPANSION" => {
+            if idx >= (end_idx - 1) {
+                return Err(ValkeyError::WrongArity);
+            }
+            idx += 1;
+            options.expansion = match input_args[idx].to_string_lossy().parse::<u32>() {
+                Ok(num) if (BLOOM_EXPANSION_MIN..=BLOOM_EXPANSION_MAX).contains(&num) => num,
+                _ => {
+                    return Err(BloomError::BadExpansion.into());
+                }
+            };
+        }
+        "HASH" => {
+            if idx >= (end_idx - 1) {
+                return Err(ValkeyError::WrongArity);
+            }
+            idx += 1;
+            options.hash_function =
+                match BloomHashFunction::from_name(&input_args[idx].to_string_lossy()) {
+                    Some(hash_function) => hash_function,
+                    None => {
+                        return Err(BloomError::BadHashFunction.into());
+                    }
+                };
+        }
+        _ => {
+            return Err(BloomError::UnknownArgument.into());
+        }
+    }
+    Ok(idx)
+}
+
+/// Parse the creation options and the add mode starting at `idx` up to the ITEMS argument.
+/// Returns the index of the first item.
+fn parse_add_options(
+    input_args: &[ValkeyString],
+    mut idx: usize,
+    options: &mut BloomAddOptions,
+) -> Result<usize, ValkeyError> {
+    let argc = input_args.len();
+    while idx < argc {
+        match input_args[idx].to_string_lossy().to_uppercase().as_str() {
+            "ITEMS" => {
+                idx += 1;
+                break;
+            }
+            "ATOMIC" | "CONTINUE" if options.mode != BloomAddMode::StopOnError => {
+                return Err(BloomError::AtomicAndContinue.into());
+            }
+            "ATOMIC" => {
+                options.mode = BloomAddMode::Atomic;
+            }
+            "CONTINUE" => {
+                options.mode = BloomAddMode::Continue;
+            }
+            _ => {
+                idx = parse_create_option(input_args, idx, argc, options)?;
+            }
+        }
+        idx += 1;
+    }
+    if idx == argc {
+        // No ITEMS argument after the creation options
+        return Err(ValkeyError::WrongArity);
+    }
+    Ok(idx)
+}
+
+/// Returns the bloom object of a key opened for reading.
+/// Objects loaded from RDB files of the RedisBloom module keep its data type, so both data types are accepted.
+fn get_bloom_value(key: &ValkeyKey) -> Result<Option<&BloomFilterType>, ValkeyError> {
+    key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE)
+        .or_else(|_| key.get_value::<BloomFilterType>(&REDISBLOOM_FILTER_TYPE))
+}
+
+/// Returns the bloom object of a key opened for writing, which is of either bloom data type.
+fn get_bloom_value_mut<'a>(
+    key: &ValkeyKeyWritable,
+) -> Result<Option<&'a mut BloomFilterType>, ValkeyError> {
+    key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE)
+        .or_else(|_| key.get_value::<BloomFilterType>(&REDISBLOOM_FILTER_TYPE))
+}
+
+/// Check that the keys of a multi-key command hash to the same slot when the server runs in cluster mode.
+/// The server rejects such commands of clients before they run, but not the ones called by other modules or
+/// by scripts which allow cross slot keys, and the keys of another slot can be owned by another node.
+/// The slots are computed by the server, which leaves the check to the key specs when it does not export the API.
+fn validate_keys_in_same_slot(ctx: &Context, keys: &[ValkeyString]) -> Result<(), ValkeyError> {
+    if !ctx.get_flags().contains(ContextFlags::CLUSTER) {
+        return Ok(());
+    }
+    let Some(cluster_key_slot) = (unsafe { raw::ValkeyModule_ClusterKeySlot }) else {
+        return Ok(());
+    };
+    let mut slots = keys
+        .iter()
+        .map(|key| unsafe { cluster_key_slot(key.inner as *mut raw::ValkeyModuleString) });
+    match slots.next() {
+        Some(slot) if slots.any(|other_slot| other_slot != slot) => {
+            Err(BloomError::CrossSlot.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Add the items starting at `item_idx` to the bloom object, creating it with the creation options if it does not exist.
+fn add_items_creating_filter(
+    ctx: &Context,
+    input_args: &[ValkeyString],
+    filter_name: &ValkeyString,
+    item_idx: usize,
+    multi: bool,
+    options: &BloomAddOptions,
+) -> ValkeyResult {
+    let argc = input_args.len();
     // If the filter does not exist, create one
     let filter_key = ctx.open_key_writable(filter_name);
-    let value = match filter_key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE) {
+    let value = match get_bloom_value_mut(&filter_key) {
         Ok(v) => v,
         Err(_) => {
-            return Err(ValkeyError::Str(utils::ERROR));
+            return Err(BloomError::WrongType.into());
         }
     };
     let mut add_succeeded = false;
@@ -94,32 +353,41 @@ pub fn bloom_filter_add_value(
             let response = handle_bloom_add(
                 input_args,
                 argc,
-                curr_cmd_idx,
+                item_idx,
                 bf,
                 multi,
+                options.mode,
                 &mut add_succeeded,
             );
             replicate_and_notify_events(ctx, filter_name, add_succeeded, false);
             response
         }
         None => {
+            if options.nocreate {
+                return Err(BloomError::NotFound.into());
+            }
             // Instantiate empty bloom filter.
-            let fp_rate = configs::BLOOM_FP_RATE_DEFAULT;
-            let capacity = configs::BLOOM_CAPACITY.load(Ordering::Relaxed) as u32;
-            let expansion = configs::BLOOM_EXPANSION.load(Ordering::Relaxed) as u32;
-            let mut bf = BloomFilterType::new_reserved(fp_rate, capacity, expansion);
+            let mut bf = BloomFilterType::new_reserved(
+                options.fp_rate,
+                options.capacity,
+                options.expansion,
+                BloomFilterLayout::Standard,
+                options.hash_function,
+            )?;
+            // Nothing was added if the command failed as a whole, so the bloom object is not created.
             let response = handle_bloom_add(
                 input_args,
                 argc,
-                curr_cmd_idx,
+                item_idx,
                 &mut bf,
                 multi,
+                options.mode,
                 &mut add_succeeded,
-            );
+            )?;
             match filter_key.set_value(&BLOOM_FILTER_TYPE, bf) {
                 Ok(()) => {
                     replicate_and_notify_events(ctx, filter_name, add_succeeded, true);
-                    response
+                    Ok(response)
                 }
                 Err(_) => Err(ValkeyError::Str(utils::ERROR)),
             }
@@ -145,21 +413,36 @@ pub fn bloom_filter_exists(
     multi: bool,
 ) -> ValkeyResult {
     let argc = input_args.len();
-    if (!multi && argc != 3) || argc < 3 {
+    if (!multi && !(3..=4).contains(&argc)) || argc < 3 {
         return Err(ValkeyError::WrongArity);
     }
     let mut curr_cmd_idx = 1;
     // Parse the filter name
     let filter_name = &input_args[curr_cmd_idx];
     curr_cmd_idx += 1;
+    // Parse whether a missing bloom object is an error rather than a negative lookup
+    let mut strict = false;
+    if !multi && argc == 4 {
+        match input_args[3].to_string_lossy().to_uppercase().as_str() {
+            "STRICT" => {
+                strict = true;
+            }
+            _ => {
+                return Err(BloomError::UnknownArgument.into());
+            }
+        }
+    }
     // Parse the value to be checked whether it exists in the filter
     let filter_key = ctx.open_key(filter_name);
-    let value = match filter_key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE) {
+    let value = match get_bloom_value(&filter_key) {
         Ok(v) => v,
         Err(_) => {
-            return Err(ValkeyError::Str(utils::ERROR));
+            return Err(BloomError::WrongType.into());
         }
     };
+    if strict && value.is_none() {
+        return Err(BloomError::NotFound.into());
+    }
     if !multi {
         let item = input_args[curr_cmd_idx].as_slice();
         return Ok(handle_item_exists(value, item));
@@ -173,30 +456,107 @@ pub fn bloom_filter_exists(
     Ok(ValkeyValue::Array(result))
 }
 
+pub fn bloom_filter_mkeyexists(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    if argc < 3 {
+        return Err(ValkeyError::WrongArity);
+    }
+    validate_keys_in_same_slot(ctx, &input_args[2..])?;
+    let mut curr_cmd_idx = 1;
+    // Parse the value to be checked whether it exists in each of the filters
+    let item = input_args[curr_cmd_idx].as_slice();
+    curr_cmd_idx += 1;
+    let mut result = Vec::new();
+    while curr_cmd_idx < argc {
+        let filter_name = &input_args[curr_cmd_idx];
+        let filter_key = ctx.open_key(filter_name);
+        let value = match get_bloom_value(&filter_key) {
+            Ok(v) => v,
+            Err(_) => {
+                return Err(BloomError::WrongType.into());
+            }
+        };
+        result.push(handle_item_exists(value, item));
+        curr_cmd_idx += 1;
+    }
+    Ok(ValkeyValue::Array(result))
+}
+
 pub fn bloom_filter_card(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
     let argc = input_args.len();
-    if argc != 2 {
+    if !(2..=3).contains(&argc) {
         return Err(ValkeyError::WrongArity);
     }
-    let curr_cmd_idx = 1;
+    let mut curr_cmd_idx = 1;
     // Parse the filter name
     let filter_name = &input_args[curr_cmd_idx];
+    curr_cmd_idx += 1;
+    // Parse whether the cardinality is estimated from the set bits instead of the number of items added
+    let mut estimate = false;
+    if curr_cmd_idx < argc {
+        match input_args[curr_cmd_idx]
+            .to_string_lossy()
+            .to_uppercase()
+            .as_str()
+        {
+            "ESTIMATE" => {
+                estimate = true;
+            }
+            _ => {
+                return Err(BloomError::UnknownArgument.into());
+            }
+        }
+    }
     let filter_key = ctx.open_key(filter_name);
-    let value = match filter_key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE) {
+    let value = match get_bloom_value(&filter_key) {
         Ok(v) => v,
         Err(_) => {
-            return Err(ValkeyError::Str(utils::ERROR));
+            return Err(BloomError::WrongType.into());
         }
     };
     match value {
+        Some(val) if estimate => Ok(ValkeyValue::Integer(val.estimated_cardinality())),
         Some(val) => Ok(ValkeyValue::Integer(val.cardinality())),
         None => Ok(ValkeyValue::Integer(0)),
     }
 }
 
+pub fn bloom_filter_intercard(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    if argc != 3 {
+        return Err(ValkeyError::WrongArity);
+    }
+    validate_keys_in_same_slot(ctx, &input_args[1..3])?;
+    // Parse the names of both filters
+    let filter_key_one = ctx.open_key(&input_args[1]);
+    let filter_key_two = ctx.open_key(&input_args[2]);
+    let value_one = match get_bloom_value(&filter_key_one) {
+        Ok(v) => v,
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    let value_two = match get_bloom_value(&filter_key_two) {
+        Ok(v) => v,
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    match (value_one, value_two) {
+        (Some(val_one), Some(val_two)) => {
+            match val_one.estimated_intersection_cardinality(val_two) {
+                Ok(estimate) => Ok(ValkeyValue::Integer(estimate)),
+                Err(err) => Err(err.into()),
+            }
+        }
+        // Nothing was added to a bloom object which does not exist.
+        _ => Ok(ValkeyValue::Integer(0)),
+    }
+}
+
 pub fn bloom_filter_reserve(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
     let argc = input_args.len();
-    if !(4..=6).contains(&argc) {
+    if !(4..=10).contains(&argc) {
         return Err(ValkeyError::WrongArity);
     }
     let mut curr_cmd_idx = 1;
@@ -207,60 +567,106 @@ pub fn bloom_filter_reserve(ctx: &Context, input_args: &[ValkeyString]) -> Valke
     let fp_rate = match input_args[curr_cmd_idx].to_string_lossy().parse::<f32>() {
         Ok(num) if num > BLOOM_FP_RATE_MIN && num < BLOOM_FP_RATE_MAX => num,
         Ok(num) if !(num > BLOOM_FP_RATE_MIN && num < BLOOM_FP_RATE_MAX) => {
-            return Err(ValkeyError::Str(utils::ERROR_RATE_RANGE));
+            return Err(BloomError::ErrorRateRange.into());
         }
         _ => {
-            return Err(ValkeyError::Str(utils::BAD_ERROR_RATE));
+            return Err(BloomError::BadErrorRate.into());
         }
     };
     curr_cmd_idx += 1;
     // Parse the capacity
-    let capacity = match input_args[curr_cmd_idx].to_string_lossy().parse::<u32>() {
+    let capacity = match input_args[curr_cmd_idx].to_string_lossy().parse::<u64>() {
         Ok(num) if (BLOOM_CAPACITY_MIN..=BLOOM_CAPACITY_MAX).contains(&num) => num,
         Ok(0) => {
-            return Err(ValkeyError::Str(utils::CAPACITY_LARGER_THAN_0));
+            return Err(BloomError::CapacityLargerThan0.into());
         }
         _ => {
-            return Err(ValkeyError::Str(utils::BAD_CAPACITY));
+            return Err(BloomError::BadCapacity.into());
         }
     };
     curr_cmd_idx += 1;
     let mut expansion = configs::BLOOM_EXPANSION.load(Ordering::Relaxed) as u32;
-    if argc > 4 {
+    let mut scaling_parsed = false;
+    let mut layout = BloomFilterLayout::Standard;
+    let mut hash_function = configs::default_hash_function();
+    while curr_cmd_idx < argc {
         match input_args[curr_cmd_idx]
             .to_string_lossy()
             .to_uppercase()
             .as_str()
         {
-            "NONSCALING" if argc == 5 => {
+            // Only one of NONSCALING and EXPANSION can be provided.
+            "NONSCALING" | "EXPANSION" if scaling_parsed => {
+                return Err(ValkeyError::WrongArity);
+            }
+            "NONSCALING" => {
+                scaling_parsed = true;
                 expansion = 0;
             }
-            "EXPANSION" if argc == 6 => {
+            "EXPANSION" => {
+                if curr_cmd_idx >= (argc - 1) {
+                    return Err(ValkeyError::WrongArity);
+                }
+                scaling_parsed = true;
                 curr_cmd_idx += 1;
                 expansion = match input_args[curr_cmd_idx].to_string_lossy().parse::<u32>() {
                     Ok(num) if (BLOOM_EXPANSION_MIN..=BLOOM_EXPANSION_MAX).contains(&num) => num,
                     _ => {
-                        return Err(ValkeyError::Str(utils::BAD_EXPANSION));
+                        return Err(BloomError::BadExpansion.into());
                     }
                 };
             }
+            "LAYOUT" => {
+                if curr_cmd_idx >= (argc - 1) {
+                    return Err(ValkeyError::WrongArity);
+                }
+                curr_cmd_idx += 1;
+                layout =
+                    match BloomFilterLayout::from_name(&input_args[curr_cmd_idx].to_string_lossy())
+                    {
+                        Some(layout) => layout,
+                        None => {
+                            return Err(BloomError::BadLayout.into());
+                        }
+                    };
+            }
+            "HASH" => {
+                if curr_cmd_idx >= (argc - 1) {
+                    return Err(ValkeyError::WrongArity);
+                }
+                curr_cmd_idx += 1;
+                hash_function =
+                    match BloomHashFunction::from_name(&input_args[curr_cmd_idx].to_string_lossy())
+                    {
+                        Some(hash_function) => hash_function,
+                        None => {
+                            return Err(BloomError::BadHashFunction.into());
+                        }
+                    };
+            }
+            // Arguments which follow the options are extra arguments, as before the options were added.
+            _ if curr_cmd_idx > 4 => {
+                return Err(ValkeyError::WrongArity);
+            }
             _ => {
-                return Err(ValkeyError::Str(utils::ERROR));
+                return Err(BloomError::UnknownArgument.into());
             }
         }
+        curr_cmd_idx += 1;
     }
     // If the filter does not exist, create one
     let filter_key = ctx.open_key_writable(filter_name);
-    let value = match filter_key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE) {
+    let value = match get_bloom_value_mut(&filter_key) {
         Ok(v) => v,
         Err(_) => {
-            return Err(ValkeyError::Str(utils::ERROR));
+            return Err(BloomError::WrongType.into());
         }
     };
     match value {
-        Some(_) => Err(ValkeyError::Str(utils::ITEM_EXISTS)),
+        Some(_) => Err(BloomError::ItemExists.into()),
         None => {
-            let bloom = BloomFilterType::new_reserved(fp_rate, capacity, expansion);
+            let bloom =
+                BloomFilterType::new_reserved(fp_rate, capacity, expansion, layout, hash_function)?;
             match filter_key.set_value(&BLOOM_FILTER_TYPE, bloom) {
                 Ok(()) => {
                     replicate_and_notify_events(ctx, filter_name, false, true);
@@ -272,115 +678,255 @@ pub fn bloom_filter_reserve(ctx: &Context, input_args: &[ValkeyString]) -> Valke
     }
 }
 
-pub fn bloom_filter_insert(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+pub fn bloom_filter_clear(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
     let argc = input_args.len();
-    // At the very least, we need: BF.INSERT <key> ITEMS <item>
-    if argc < 4 {
+    if argc != 2 {
         return Err(ValkeyError::WrongArity);
     }
-    let mut idx = 1;
+    let curr_cmd_idx = 1;
     // Parse the filter name
-    let filter_name = &input_args[idx];
-    idx += 1;
-    let mut fp_rate = configs::BLOOM_FP_RATE_DEFAULT;
-    let mut capacity = configs::BLOOM_CAPACITY.load(Ordering::Relaxed) as u32;
-    let mut expansion = configs::BLOOM_EXPANSION.load(Ordering::Relaxed) as u32;
-    let mut nocreate = false;
-    while idx < argc {
-        match input_args[idx].to_string_lossy().to_uppercase().as_str() {
-            "ERROR" => {
-                if idx >= (argc - 1) {
-                    return Err(ValkeyError::WrongArity);
-                }
-                idx += 1;
-                fp_rate = match input_args[idx].to_string_lossy().parse::<f32>() {
-                    Ok(num) if num > BLOOM_FP_RATE_MIN && num < BLOOM_FP_RATE_MAX => num,
-                    Ok(num) if !(num > BLOOM_FP_RATE_MIN && num < BLOOM_FP_RATE_MAX) => {
-                        return Err(ValkeyError::Str(utils::ERROR_RATE_RANGE));
-                    }
-                    _ => {
-                        return Err(ValkeyError::Str(utils::BAD_ERROR_RATE));
-                    }
-                };
-            }
+    let filter_name = &input_args[curr_cmd_idx];
+    let filter_key = ctx.open_key_writable(filter_name);
+    let value = match get_bloom_value_mut(&filter_key) {
+        Ok(v) => v,
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    match value {
+        Some(bf) => {
+            bf.clear();
+            ctx.replicate_verbatim();
+            ctx.notify_keyspace_event(NotifyEvent::GENERIC, utils::CLEAR_EVENT, filter_name);
+            VALKEY_OK
+        }
+        None => Err(BloomError::NotFound.into()),
+    }
+}
+
+pub fn bloom_filter_compact(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    if argc != 3 && argc != 5 {
+        return Err(ValkeyError::WrongArity);
+    }
+    validate_keys_in_same_slot(ctx, &input_args[1..3])?;
+    let mut curr_cmd_idx = 1;
+    // Parse the filter names
+    let filter_name = &input_args[curr_cmd_idx];
+    curr_cmd_idx += 1;
+    let temp_filter_name = &input_args[curr_cmd_idx];
+    curr_cmd_idx += 1;
+    let filter_key = ctx.open_key(filter_name);
+    let value = match get_bloom_value(&filter_key) {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            return Err(BloomError::NotFound.into());
+        }
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    // By default, the rebuilt filter is sized for the number of items in the object.
+    let mut capacity = value.cardinality().clamp(1, BLOOM_CAPACITY_MAX as i64) as u64;
+    if curr_cmd_idx < argc {
+        match input_args[curr_cmd_idx]
+            .to_string_lossy()
+            .to_uppercase()
+            .as_str()
+        {
             "CAPACITY" => {
-                if idx >= (argc - 1) {
-                    return Err(ValkeyError::WrongArity);
-                }
-                idx += 1;
-                capacity = match input_args[idx].to_string_lossy().parse::<u32>() {
+                curr_cmd_idx += 1;
+                capacity = match input_args[curr_cmd_idx].to_string_lossy().parse::<u64>() {
                     Ok(num) if (BLOOM_CAPACITY_MIN..=BLOOM_CAPACITY_MAX).contains(&num) => num,
                     Ok(0) => {
-                        return Err(ValkeyError::Str(utils::CAPACITY_LARGER_THAN_0));
+                        return Err(BloomError::CapacityLargerThan0.into());
                     }
                     _ => {
-                        return Err(ValkeyError::Str(utils::BAD_CAPACITY));
+                        return Err(BloomError::BadCapacity.into());
                     }
                 };
             }
-            "NOCREATE" => {
-                nocreate = true;
-            }
-            "NONSCALING" => {
-                expansion = 0;
-            }
-            "EXPANSION" => {
-                if idx >= (argc - 1) {
-                    return Err(ValkeyError::WrongArity);
-                }
-                idx += 1;
-                expansion = match input_args[idx].to_string_lossy().parse::<u32>() {
-                    Ok(num) if (BLOOM_EXPANSION_MIN..=BLOOM_EXPANSION_MAX).contains(&num) => num,
-                    _ => {
-                        return Err(ValkeyError::Str(utils::BAD_EXPANSION));
-                    }
-                };
-            }
-            "ITEMS" => {
-                idx += 1;
-                break;
-            }
             _ => {
-                return Err(ValkeyError::Str(utils::UNKNOWN_ARGUMENT));
+                return Err(BloomError::UnknownArgument.into());
             }
         }
-        idx += 1;
     }
-    if idx == argc {
-        // No ITEMS argument from the insert command
+    let temp_filter_key = ctx.open_key_writable(temp_filter_name);
+    match get_bloom_value_mut(&temp_filter_key) {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return Err(BloomError::ItemExists.into());
+        }
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    let bloom = BloomFilterType::create_compacted_from(value, capacity)?;
+    match temp_filter_key.set_value(&BLOOM_FILTER_TYPE, bloom) {
+        Ok(()) => {
+            ctx.replicate_verbatim();
+            ctx.notify_keyspace_event(NotifyEvent::GENERIC, utils::COMPACT_EVENT, temp_filter_name);
+            VALKEY_OK
+        }
+        Err(_) => Err(ValkeyError::Str(utils::ERROR)),
+    }
+}
+
+pub fn bloom_filter_swap(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    if argc != 3 {
         return Err(ValkeyError::WrongArity);
     }
-    // If the filter does not exist, create one
+    validate_keys_in_same_slot(ctx, &input_args[1..3])?;
+    let mut curr_cmd_idx = 1;
+    // Parse the filter names
+    let filter_name = &input_args[curr_cmd_idx];
+    curr_cmd_idx += 1;
+    let temp_filter_name = &input_args[curr_cmd_idx];
+    let temp_filter_key = ctx.open_key_writable(temp_filter_name);
+    let temp_value = match get_bloom_value_mut(&temp_filter_key) {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            return Err(BloomError::NotFound.into());
+        }
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    if filter_name.as_slice() == temp_filter_name.as_slice() {
+        return VALKEY_OK;
+    }
     let filter_key = ctx.open_key_writable(filter_name);
     let value = match filter_key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE) {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            return Err(BloomError::NotFound.into());
+        }
+        Err(_) => {
+            // An object of the RedisBloom data type is replaced by an empty object of the native data type, which
+            // the rebuilt object is then swapped into. Other types are rejected before the key is modified.
+            match filter_key.get_value::<BloomFilterType>(&REDISBLOOM_FILTER_TYPE) {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    return Err(BloomError::NotFound.into());
+                }
+                Err(_) => {
+                    return Err(BloomError::WrongType.into());
+                }
+            };
+            filter_key.delete()?;
+            let empty_bloom = BloomFilterType::from_existing(
+                temp_value.expansion,
+                temp_value.fp_rate,
+                temp_value.layout,
+                temp_value.hash_function,
+                Vec::new(),
+            );
+            if filter_key
+                .set_value(&BLOOM_FILTER_TYPE, empty_bloom)
+                .is_err()
+            {
+                return Err(ValkeyError::Str(utils::ERROR));
+            }
+            match filter_key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE) {
+                Ok(Some(v)) => v,
+                _ => {
+                    return Err(ValkeyError::Str(utils::ERROR));
+                }
+            }
+        }
+    };
+    // Every fallible operation on the key is done, so the rebuilt object is moved into it last, by swapping the
+    // objects of both keys without copying their filters. The previous object is freed with the temporary key.
+    std::mem::swap(value, temp_value);
+    temp_filter_key.delete()?;
+    ctx.replicate_verbatim();
+    ctx.notify_keyspace_event(NotifyEvent::GENERIC, utils::SWAP_EVENT, filter_name);
+    ctx.notify_keyspace_event(NotifyEvent::GENERIC, "del", temp_filter_name);
+    VALKEY_OK
+}
+
+pub fn bloom_filter_merge(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    if argc < 3 {
+        return Err(ValkeyError::WrongArity);
+    }
+    validate_keys_in_same_slot(ctx, &input_args[1..])?;
+    let mut curr_cmd_idx = 1;
+    // Parse the filter name of the destination
+    let filter_name = &input_args[curr_cmd_idx];
+    curr_cmd_idx += 1;
+    let filter_key = ctx.open_key_writable(filter_name);
+    let value = match get_bloom_value_mut(&filter_key) {
         Ok(v) => v,
         Err(_) => {
-            return Err(ValkeyError::Str(utils::ERROR));
+            return Err(BloomError::WrongType.into());
         }
     };
-    let mut add_succeeded = false;
-    match value {
-        Some(bf) => {
-            let response = handle_bloom_add(input_args, argc, idx, bf, true, &mut add_succeeded);
-            replicate_and_notify_events(ctx, filter_name, add_succeeded, false);
-            response
+    // The sources are merged into a copy of the destination object (or of the first source when the destination
+    // does not exist), so that the destination is left unchanged when any of the sources cannot be merged.
+    let filter_exists = value.is_some();
+    let mut merged = value.as_deref().map(BloomFilterType::create_copy_from);
+    while curr_cmd_idx < argc {
+        let source_filter_name = &input_args[curr_cmd_idx];
+        curr_cmd_idx += 1;
+        // Merging the destination object into itself does not add any item to it.
+        if filter_exists && source_filter_name.as_slice() == filter_name.as_slice() {
+            continue;
         }
-        None => {
-            if nocreate {
-                return Err(ValkeyError::Str(utils::NOT_FOUND));
+        let source_filter_key = ctx.open_key(source_filter_name);
+        let source_value = match get_bloom_value(&source_filter_key) {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                return Err(BloomError::NotFound.into());
             }
-            let mut bf = BloomFilterType::new_reserved(fp_rate, capacity, expansion);
-            let response =
-                handle_bloom_add(input_args, argc, idx, &mut bf, true, &mut add_succeeded);
-            match filter_key.set_value(&BLOOM_FILTER_TYPE, bf) {
-                Ok(()) => {
-                    replicate_and_notify_events(ctx, filter_name, add_succeeded, true);
-                    response
+            Err(_) => {
+                return Err(BloomError::WrongType.into());
+            }
+        };
+        match merged.as_mut() {
+            Some(bf) => {
+                if let Err(err) = bf.merge_from(source_value) {
+                    return Err(err.into());
                 }
-                Err(_) => Err(ValkeyError::Str(utils::ERROR)),
             }
+            None => merged = Some(BloomFilterType::create_copy_from(source_value)),
         }
     }
+    let merged = match merged {
+        Some(bf) => bf,
+        None => return VALKEY_OK,
+    };
+    let result = match value {
+        Some(bf) => {
+            *bf = merged;
+            Ok(())
+        }
+        None => filter_key.set_value(&BLOOM_FILTER_TYPE, merged),
+    };
+    match result {
+        Ok(()) => {
+            ctx.replicate_verbatim();
+            ctx.notify_keyspace_event(NotifyEvent::GENERIC, utils::MERGE_EVENT, filter_name);
+            VALKEY_OK
+        }
+        Err(_) => Err(ValkeyError::Str(utils::ERROR)),
+    }
+}
+
+pub fn bloom_filter_insert(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    // At the very least, we need: BF.INSERT <key> ITEMS <item>
+    if argc < 4 {
+        return Err(ValkeyError::WrongArity);
+    }
+    let mut idx = 1;
+    // Parse the filter name
+    let filter_name = &input_args[idx];
+    idx += 1;
+    let mut add_options = BloomAddOptions::from_configs();
+    idx = parse_add_options(input_args, idx, &mut add_options)?;
+    add_items_creating_filter(ctx, input_args, filter_name, idx, true, &add_options)
 }
 
 pub fn bloom_filter_info(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
@@ -393,10 +939,10 @@ pub fn bloom_filter_info(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyRe
     let filter_name = &input_args[curr_cmd_idx];
     curr_cmd_idx += 1;
     let filter_key = ctx.open_key(filter_name);
-    let value = match filter_key.get_value::<BloomFilterType>(&BLOOM_FILTER_TYPE) {
+    let value = match get_bloom_value(&filter_key) {
         Ok(v) => v,
         Err(_) => {
-            return Err(ValkeyError::Str(utils::ERROR));
+            return Err(BloomError::WrongType.into());
         }
     };
     match value {
@@ -410,13 +956,19 @@ pub fn bloom_filter_info(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyRe
                 "SIZE" => Ok(ValkeyValue::Integer(val.memory_usage() as i64)),
                 "FILTERS" => Ok(ValkeyValue::Integer(val.filters.len() as i64)),
                 "ITEMS" => Ok(ValkeyValue::Integer(val.cardinality())),
+                "ESTIMATE" => Ok(ValkeyValue::Integer(val.estimated_cardinality())),
                 "EXPANSION" => {
                     if val.expansion == 0 {
                         return Ok(ValkeyValue::Null);
                     }
                     Ok(ValkeyValue::Integer(val.expansion as i64))
                 }
-                _ => Err(ValkeyError::Str(utils::INVALID_INFO_VALUE)),
+                "LAYOUT" => Ok(ValkeyValue::SimpleStringStatic(val.layout.as_str())),
+                "HASH" => Ok(ValkeyValue::SimpleStringStatic(val.hash_function.as_str())),
+                "MAXFILTERS" => Ok(ValkeyValue::Integer(
+                    configs::max_filters_per_object() as i64
+                )),
+                _ => Err(BloomError::InvalidInfoValue.into()),
             }
         }
         Some(val) if argc == 2 => {
@@ -438,6 +990,87 @@ pub fn bloom_filter_info(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyRe
             }
             Ok(ValkeyValue::Array(result))
         }
-        _ => Err(ValkeyError::Str(utils::NOT_FOUND)),
+        _ => Err(BloomError::NotFound.into()),
+    }
+}
+
+/// Parse the iterator of BF.SCANDUMP and BF.LOADCHUNK.
+fn parse_iterator(arg: &ValkeyString) -> Result<u64, ValkeyError> {
+    match arg.to_string_lossy().parse::<u64>() {
+        Ok(iterator) => Ok(iterator),
+        Err(_) => Err(BloomError::BadIterator.into()),
+    }
+}
+
+pub fn bloom_filter_scandump(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    if argc != 3 {
+        return Err(ValkeyError::WrongArity);
+    }
+    let filter_name = &input_args[1];
+    let iterator = parse_iterator(&input_args[2])?;
+    let filter_key = ctx.open_key(filter_name);
+    let value = match get_bloom_value(&filter_key) {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            return Err(BloomError::NotFound.into());
+        }
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    // The header is replied first, then the chunks of the bitmaps, and an empty chunk once they are all replied.
+    let (next_iterator, data) = if iterator == 0 {
+        (1, value.encoded_header())
+    } else {
+        value.encoded_chunk(iterator).unwrap_or((0, Vec::new()))
+    };
+    Ok(ValkeyValue::Array(vec![
+        ValkeyValue::Integer(next_iterator as i64),
+        ValkeyValue::StringBuffer(data),
+    ]))
+}
+
+pub fn bloom_filter_loadchunk(ctx: &Context, input_args: &[ValkeyString]) -> ValkeyResult {
+    let argc = input_args.len();
+    if argc != 4 {
+        return Err(ValkeyError::WrongArity);
+    }
+    let filter_name = &input_args[1];
+    let iterator = parse_iterator(&input_args[2])?;
+    if iterator == 0 {
+        return Err(BloomError::BadIterator.into());
+    }
+    let data = input_args[3].as_slice();
+    let filter_key = ctx.open_key_writable(filter_name);
+    let value = match get_bloom_value_mut(&filter_key) {
+        Ok(v) => v,
+        Err(_) => {
+            return Err(BloomError::WrongType.into());
+        }
+    };
+    // The header, replied with the iterator 1, creates the object with empty filters which the chunks fill.
+    match value {
+        Some(_) if iterator == 1 => {
+            return Err(BloomError::ItemExists.into());
+        }
+        Some(bf) => bf.load_encoded_chunk(iterator, data)?,
+        None if iterator == 1 => {
+            // Objects replayed from the AOF (including its rewrite of objects above the memory limit) or from the
+            // primary were already accepted, so only the headers received from clients are validated.
+            let flags = ctx.get_flags();
+            let check_memory_limit =
+                !flags.contains(ContextFlags::LOADING) && !flags.contains(ContextFlags::REPLICATED);
+            let bloom = BloomFilterType::from_encoded_header(data, check_memory_limit)?;
+            if filter_key.set_value(&BLOOM_FILTER_TYPE, bloom).is_err() {
+                return Err(ValkeyError::Str(utils::ERROR));
+            }
+        }
+        None => {
+            return Err(BloomError::NotFound.into());
+        }
     }
+    ctx.replicate_verbatim();
+    ctx.notify_keyspace_event(NotifyEvent::GENERIC, utils::LOADCHUNK_EVENT, filter_name);
+    VALKEY_OK
 }
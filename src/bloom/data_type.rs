@@ -1,25 +1,26 @@
-use crate::bloom::utils::BloomFilter;
 use crate::bloom::utils::BloomFilterType;
-use crate::configs::{
-    FIXED_SIP_KEY_ONE_A, FIXED_SIP_KEY_ONE_B, FIXED_SIP_KEY_TWO_A, FIXED_SIP_KEY_TWO_B,
-};
+use crate::bloom::utils::BloomHashFunction;
+use crate::configs;
 use crate::wrapper::bloom_callback;
-use crate::MODULE_NAME;
+use crate::{MODULE_NAME, MODULE_VERSION};
 use std::os::raw::c_int;
+use std::sync::atomic::Ordering;
+use valkey_bloom_core::metrics;
+use valkey_bloom_core::rdb::{
+    self, RdbLoadError, RdbReader, RdbWriter, BLOOM_FILTER_TYPE_ENCODING_VERSION,
+    BLOOM_FILTER_TYPE_NAME, REDISBLOOM_FILTER_TYPE_ENCODING_VERSION, REDISBLOOM_FILTER_TYPE_NAME,
+};
 use valkey_module::native_types::ValkeyType;
-use valkey_module::{logging, raw};
-
-const BLOOM_FILTER_TYPE_ENCODING_VERSION: i32 = 0;
+use valkey_module::{logging, raw, RedisBuffer};
 
 pub static BLOOM_FILTER_TYPE: ValkeyType = ValkeyType::new(
-    "bloomfltr",
+    BLOOM_FILTER_TYPE_NAME,
     BLOOM_FILTER_TYPE_ENCODING_VERSION,
     raw::RedisModuleTypeMethods {
         version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
         rdb_load: Some(bloom_callback::bloom_rdb_load),
         rdb_save: Some(bloom_callback::bloom_rdb_save),
-        // TODO
-        aof_rewrite: None,
+        aof_rewrite: Some(bloom_callback::bloom_aof_rewrite),
 
         mem_usage: Some(bloom_callback::bloom_mem_usage),
         // TODO
@@ -27,13 +28,15 @@ pub static BLOOM_FILTER_TYPE: ValkeyType = ValkeyType::new(
         free: Some(bloom_callback::bloom_free),
 
         aux_load: Some(bloom_callback::bloom_aux_load),
-        // Callback not needed as there is no AUX (out of keyspace) data to be saved.
+        // The version 2 callback leaves no aux data in RDB files without bloom objects when it saves nothing,
+        // so that they can be loaded by servers without the module.
         aux_save: None,
-        aux_save2: None,
+        aux_save2: Some(bloom_callback::bloom_aux_save),
         aux_save_triggers: raw::Aux::Before as i32,
 
         free_effort: Some(bloom_callback::bloom_free_effort),
-        // Callback not needed as it just notifies us when a bloom item is about to be freed.
+        // Callback not needed as the module metrics are atomics which are updated when a bloom item is dropped,
+        // so they are accurate whether the item is freed on the main thread or by lazyfree in a background thread.
         unlink: None,
         copy: Some(bloom_callback::bloom_copy),
         defrag: Some(bloom_callback::bloom_defrag),
@@ -46,74 +49,202 @@ pub static BLOOM_FILTER_TYPE: ValkeyType = ValkeyType::new(
     },
 );
 
+/// Data type of the RedisBloom module, registered under its name so that RDB files and DUMP payloads produced
+/// by it can be loaded. Its bitmaps are kept as is in the redisbloom layout and hashed like RedisBloom hashes
+/// them, so the objects are saved back in the RedisBloom format.
+pub static REDISBLOOM_FILTER_TYPE: ValkeyType = ValkeyType::new(
+    REDISBLOOM_FILTER_TYPE_NAME,
+    REDISBLOOM_FILTER_TYPE_ENCODING_VERSION,
+    raw::RedisModuleTypeMethods {
+        version: raw::REDISMODULE_TYPE_METHOD_VERSION as u64,
+        rdb_load: Some(bloom_callback::redisbloom_rdb_load),
+        rdb_save: Some(bloom_callback::redisbloom_rdb_save),
+        aof_rewrite: Some(bloom_callback::bloom_aof_rewrite),
+
+        mem_usage: Some(bloom_callback::bloom_mem_usage),
+        digest: None,
+        free: Some(bloom_callback::bloom_free),
+
+        aux_load: None,
+        aux_save: None,
+        aux_save2: None,
+        aux_save_triggers: 0,
+
+        free_effort: Some(bloom_callback::bloom_free_effort),
+        unlink: None,
+        copy: Some(bloom_callback::bloom_copy),
+        defrag: Some(bloom_callback::bloom_defrag),
+
+        mem_usage2: None,
+        free_effort2: None,
+        unlink2: None,
+        copy2: None,
+    },
+);
+
 pub trait ValkeyDataType {
     fn load_from_rdb(rdb: *mut raw::RedisModuleIO, encver: i32) -> Option<BloomFilterType>;
+    fn load_from_redisbloom_rdb(
+        rdb: *mut raw::RedisModuleIO,
+        encver: i32,
+    ) -> Option<BloomFilterType>;
 }
 
 impl ValkeyDataType for BloomFilterType {
     /// Callback to load and parse RDB data of a bloom item and create it.
     fn load_from_rdb(rdb: *mut raw::RedisModuleIO, encver: i32) -> Option<BloomFilterType> {
-        let mut filters = Vec::new();
-        if encver > BLOOM_FILTER_TYPE_ENCODING_VERSION {
-            logging::log_warning(format!("{}: Cannot load bloomfltr data type of version {} because it is higher than the loaded module's bloomfltr supported version {}", MODULE_NAME, encver, BLOOM_FILTER_TYPE_ENCODING_VERSION).as_str());
-            return None;
-        }
-        let Ok(num_filters) = raw::load_unsigned(rdb) else {
-            return None;
-        };
-        let Ok(expansion) = raw::load_unsigned(rdb) else {
-            return None;
-        };
-        let Ok(fp_rate) = raw::load_float(rdb) else {
-            return None;
-        };
-        for i in 0..num_filters {
-            let Ok(bitmap) = raw::load_string_buffer(rdb) else {
-                return None;
-            };
-            let Ok(number_of_bits) = raw::load_unsigned(rdb) else {
-                return None;
-            };
-            let Ok(number_of_hash_functions) = raw::load_unsigned(rdb) else {
-                return None;
-            };
-            let Ok(capacity) = raw::load_unsigned(rdb) else {
-                return None;
-            };
-            // Only load num_items when it's the last filter
-            let num_items = if i == num_filters - 1 {
-                match raw::load_unsigned(rdb) {
-                    Ok(num_items) => num_items,
-                    Err(_) => return None,
-                }
-            } else {
-                capacity
-            };
-            let sip_keys = [
-                (FIXED_SIP_KEY_ONE_A, FIXED_SIP_KEY_ONE_B),
-                (FIXED_SIP_KEY_TWO_A, FIXED_SIP_KEY_TWO_B),
-            ];
-            let filter = BloomFilter::from_existing(
-                bitmap.as_ref(),
-                number_of_bits,
-                number_of_hash_functions as u32,
-                sip_keys,
-                num_items as u32,
-                capacity as u32,
-            );
-            filters.push(filter);
+        log_load_error(rdb::load_bloom_object(&mut ModuleRdbIo(rdb), encver))
+    }
+
+    /// Callback to load and parse RDB data of a RedisBloom item and create it.
+    fn load_from_redisbloom_rdb(
+        rdb: *mut raw::RedisModuleIO,
+        encver: i32,
+    ) -> Option<BloomFilterType> {
+        log_load_error(rdb::load_redisbloom_object(&mut ModuleRdbIo(rdb), encver))
+    }
+}
+
+/// Returns the loaded bloom item, logging why it could not be loaded otherwise.
+fn log_load_error(item: Result<BloomFilterType, RdbLoadError>) -> Option<BloomFilterType> {
+    match item {
+        Ok(item) => Some(item),
+        Err(err) => {
+            logging::log_warning(format!("{}: {}", MODULE_NAME, err).as_str());
+            None
         }
-        let item = BloomFilterType {
-            expansion: expansion as u32,
-            fp_rate,
-            filters,
-        };
-        Some(item)
     }
 }
 
-/// Load the auxiliary data outside of the regular keyspace from the RDB file
-pub fn bloom_rdb_aux_load(_rdb: *mut raw::RedisModuleIO) -> c_int {
-    logging::log_notice("Ignoring AUX fields during RDB load.");
+/// The RDB file or DUMP payload which the server is saving a bloom item to or loading it from.
+pub struct ModuleRdbIo(pub *mut raw::RedisModuleIO);
+
+impl RdbReader for ModuleRdbIo {
+    type Buffer = RedisBuffer;
+
+    fn load_unsigned(&mut self) -> Option<u64> {
+        raw::load_unsigned(self.0).ok()
+    }
+
+    fn load_float(&mut self) -> Option<f32> {
+        raw::load_float(self.0).ok()
+    }
+
+    fn load_double(&mut self) -> Option<f64> {
+        raw::load_double(self.0).ok()
+    }
+
+    fn load_string_buffer(&mut self) -> Option<RedisBuffer> {
+        raw::load_string_buffer(self.0).ok()
+    }
+}
+
+impl RdbWriter for ModuleRdbIo {
+    fn save_unsigned(&mut self, value: u64) {
+        raw::save_unsigned(self.0, value);
+    }
+
+    fn save_float(&mut self, value: f32) {
+        raw::save_float(self.0, value);
+    }
+
+    fn save_double(&mut self, value: f64) {
+        raw::save_double(self.0, value);
+    }
+
+    fn save_string_buffer(&mut self, value: &[u8]) {
+        raw::save_slice(self.0, value);
+    }
+}
+
+/// Save the auxiliary data outside of the regular keyspace to the RDB file, before the keyspace.
+/// It holds the module version and encoding version that produced the RDB, and the default configs in effect.
+/// Nothing is saved when there are no bloom objects, as there is nothing to load them with.
+pub fn bloom_rdb_aux_save(rdb: *mut raw::RedisModuleIO) {
+    if metrics::BLOOM_NUM_OBJECTS.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+    raw::save_unsigned(rdb, MODULE_VERSION as u64);
+    raw::save_unsigned(rdb, BLOOM_FILTER_TYPE_ENCODING_VERSION as u64);
+    raw::save_signed(rdb, configs::BLOOM_CAPACITY.load(Ordering::Relaxed));
+    raw::save_signed(rdb, configs::BLOOM_EXPANSION.load(Ordering::Relaxed));
+    raw::save_unsigned(rdb, configs::default_hash_function().encoding());
+    raw::save_signed(
+        rdb,
+        configs::BLOOM_MAX_FILTERS_PER_OBJ.load(Ordering::Relaxed),
+    );
+}
+
+/// Load the auxiliary data outside of the regular keyspace from the RDB file.
+/// Loading is refused when the RDB was produced by a higher module or encoding version, as its bloom objects
+/// may not be loaded correctly. Default configs which differ from the ones in effect are logged.
+pub fn bloom_rdb_aux_load(rdb: *mut raw::RedisModuleIO, when: c_int) -> c_int {
+    if when != raw::Aux::Before as c_int {
+        return raw::Status::Ok as i32;
+    }
+    let Ok(module_version) = raw::load_unsigned(rdb) else {
+        return raw::Status::Err as i32;
+    };
+    if module_version > MODULE_VERSION as u64 {
+        logging::log_warning(format!("{}: Cannot load RDB produced by module version {} because it is higher than the loaded module's version {}", MODULE_NAME, module_version, MODULE_VERSION).as_str());
+        return raw::Status::Err as i32;
+    }
+    let Ok(encoding_version) = raw::load_unsigned(rdb) else {
+        return raw::Status::Err as i32;
+    };
+    if encoding_version > BLOOM_FILTER_TYPE_ENCODING_VERSION as u64 {
+        logging::log_warning(format!("{}: Cannot load RDB produced with bloomfltr encoding version {} because it is higher than the loaded module's bloomfltr supported version {}", MODULE_NAME, encoding_version, BLOOM_FILTER_TYPE_ENCODING_VERSION).as_str());
+        return raw::Status::Err as i32;
+    }
+    let (Ok(capacity), Ok(expansion), Ok(hash_function), Ok(max_filters)) = (
+        raw::load_signed(rdb),
+        raw::load_signed(rdb),
+        raw::load_unsigned(rdb),
+        raw::load_signed(rdb),
+    ) else {
+        return raw::Status::Err as i32;
+    };
+    let saved_configs = [
+        (
+            "bloom-max-item-size",
+            capacity,
+            configs::BLOOM_CAPACITY.load(Ordering::Relaxed),
+        ),
+        (
+            "bloom-expansion-rate",
+            expansion,
+            configs::BLOOM_EXPANSION.load(Ordering::Relaxed),
+        ),
+        (
+            "bloom-max-filters-per-object",
+            max_filters,
+            configs::BLOOM_MAX_FILTERS_PER_OBJ.load(Ordering::Relaxed),
+        ),
+    ];
+    for (name, saved, current) in saved_configs {
+        if saved != current {
+            logging::log_notice(
+                format!(
+                    "{}: RDB was produced with {} {}, while the current value is {}",
+                    MODULE_NAME, name, saved, current
+                )
+                .as_str(),
+            );
+        }
+    }
+    let current_hash_function = configs::default_hash_function();
+    if hash_function != current_hash_function.encoding() {
+        let saved_name = BloomHashFunction::from_encoding(hash_function)
+            .map_or("unknown", |hash_function| hash_function.as_str());
+        logging::log_notice(
+            format!(
+                "{}: RDB was produced with bloom-hash-function {}, while the current value is {}",
+                MODULE_NAME,
+                saved_name,
+                current_hash_function.as_str()
+            )
+            .as_str(),
+        );
+    }
     raw::Status::Ok as i32
 }
@@ -1,3 +1,3 @@
 pub mod command_handler;
 pub mod data_type;
-pub mod utils;
+pub use valkey_bloom_core::utils;
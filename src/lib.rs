@@ -1,22 +1,25 @@
 use valkey_module::configuration::ConfigurationFlags;
-use valkey_module::{valkey_module, Context, Status, ValkeyResult, ValkeyString};
+use valkey_module::{valkey_module, Context, InfoContext, Status, ValkeyResult, ValkeyString};
 pub mod bloom;
 pub mod configs;
+pub mod metrics;
 pub mod wrapper;
 use crate::bloom::command_handler;
-use crate::bloom::data_type::BLOOM_FILTER_TYPE;
+use crate::bloom::data_type::{BLOOM_FILTER_TYPE, REDISBLOOM_FILTER_TYPE};
+use crate::metrics::bloom_info_handler;
 
 pub const MODULE_NAME: &str = "bf";
+pub const MODULE_VERSION: i32 = 1;
 
-fn initialize(_ctx: &Context, _args: &[ValkeyString]) -> Status {
-    Status::Ok
+fn initialize(ctx: &Context, _args: &[ValkeyString]) -> Status {
+    wrapper::command_info::set_bloom_commands_info(ctx)
 }
 
 fn deinitialize(_ctx: &Context) -> Status {
     Status::Ok
 }
 
-/// Command handler for BF.EXISTS <key> <item>
+/// Command handler for BF.EXISTS <key> <item> [STRICT]
 fn bloom_exists_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
     command_handler::bloom_filter_exists(ctx, &args, false)
 }
@@ -26,33 +29,77 @@ fn bloom_mexists_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult
     command_handler::bloom_filter_exists(ctx, &args, true)
 }
 
-/// Command handler for BF.ADD <key> <item>
+/// Command handler for BF.MKEYEXISTS <item> <key> [<key> ...]
+fn bloom_mkeyexists_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_mkeyexists(ctx, &args)
+}
+
+/// Command handler for BF.ADD <key> [ERROR <fp_error>] [CAPACITY <capacity>] [EXPANSION <expansion>] [NOCREATE] [NONSCALING]
+/// [HASH <hash_function>] <item>
 fn bloom_add_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
     command_handler::bloom_filter_add_value(ctx, &args, false)
 }
 
-/// Command handler for BF.MADD <key> <item> [<item> ...]
+/// Command handler for BF.MADD <key> [ERROR <fp_error>] [CAPACITY <capacity>] [EXPANSION <expansion>] [NOCREATE] [NONSCALING]
+/// [HASH <hash_function>] [ATOMIC | CONTINUE] [ITEMS] <item> [<item> ...]
 fn bloom_madd_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
     command_handler::bloom_filter_add_value(ctx, &args, true)
 }
 
-/// Command handler for BF.CARD <key>
+/// Command handler for BF.CARD <key> [ESTIMATE]
 fn bloom_card_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
     command_handler::bloom_filter_card(ctx, &args)
 }
 
-/// Command handler for BF.RESERVE <key> <false_positive_rate> <capacity> [EXPANSION <expansion>] | [NONSCALING]
+/// Command handler for BF.INTERCARD <key1> <key2>
+fn bloom_intercard_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_intercard(ctx, &args)
+}
+
+/// Command handler for BF.RESERVE <key> <false_positive_rate> <capacity> [EXPANSION <expansion>] | [NONSCALING] [LAYOUT <STANDARD | BLOCKED | REDISBLOOM>]
+/// [HASH <SIPHASH | XXHASH3 | MURMURHASH3 | MURMURHASH64A | MURMURHASH2>]
 fn bloom_reserve_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
     command_handler::bloom_filter_reserve(ctx, &args)
 }
 
-/// Command handler for BF.INFO <key> [CAPACITY | SIZE | FILTERS | ITEMS | EXPANSION]
+/// Command handler for BF.INFO <key> [CAPACITY | SIZE | FILTERS | ITEMS | ESTIMATE | EXPANSION | LAYOUT | HASH]
 fn bloom_info_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
     command_handler::bloom_filter_info(ctx, &args)
 }
 
+/// Command handler for BF.CLEAR <key>
+fn bloom_clear_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_clear(ctx, &args)
+}
+
+/// Command handler for BF.COMPACT <key> <temp_key> [CAPACITY <capacity>]
+fn bloom_compact_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_compact(ctx, &args)
+}
+
+/// Command handler for BF.SWAP <key> <temp_key>
+fn bloom_swap_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_swap(ctx, &args)
+}
+
+/// Command handler for BF.MERGE <destkey> <sourcekey> [<sourcekey> ...]
+fn bloom_merge_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_merge(ctx, &args)
+}
+
+/// Command handler for BF.SCANDUMP <key> <iterator>
+fn bloom_scandump_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_scandump(ctx, &args)
+}
+
+/// Command handler for BF.LOADCHUNK <key> <iterator> <data>
+fn bloom_loadchunk_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
+    command_handler::bloom_filter_loadchunk(ctx, &args)
+}
+
 /// Command handler for:
-/// BF.INSERT <key> [ERROR <fp_error>] [CAPACITY <capacity>] [EXPANSION <expansion>] [NOCREATE] [NONSCALING] ITEMS <item> [<item> ...]
+/// BF.INSERT <key> [ERROR <fp_error>] [CAPACITY <capacity>] [EXPANSION <expansion>] [NOCREATE] [NONSCALING] [HASH <hash_function>]
+/// [ATOMIC | CONTINUE] ITEMS <item> [<item> ...]
 fn bloom_insert_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult {
     command_handler::bloom_filter_insert(ctx, &args)
 }
@@ -61,33 +108,49 @@ fn bloom_insert_command(ctx: &Context, args: Vec<ValkeyString>) -> ValkeyResult
 
 valkey_module! {
     name: MODULE_NAME,
-    version: 1,
+    version: MODULE_VERSION,
     allocator: (valkey_module::alloc::ValkeyAlloc, valkey_module::alloc::ValkeyAlloc),
     data_types: [
         BLOOM_FILTER_TYPE,
+        REDISBLOOM_FILTER_TYPE,
     ],
     init: initialize,
     deinit: deinitialize,
+    info: bloom_info_handler,
+    acl_categories: [
+        "bloom",
+    ]
     commands: [
-        ["BF.ADD", bloom_add_command, "write fast deny-oom", 1, 1, 1],
-        ["BF.MADD", bloom_madd_command, "write fast deny-oom", 1, 1, 1],
-        ["BF.EXISTS", bloom_exists_command, "readonly fast", 1, 1, 1],
-        ["BF.MEXISTS", bloom_mexists_command, "readonly fast", 1, 1, 1],
-        ["BF.CARD", bloom_card_command, "readonly fast", 1, 1, 1],
-        ["BF.RESERVE", bloom_reserve_command, "write fast deny-oom", 1, 1, 1],
-        ["BF.INFO", bloom_info_command, "readonly fast", 1, 1, 1],
-        ["BF.INSERT", bloom_insert_command, "write fast deny-oom", 1, 1, 1],
+        ["BF.ADD", bloom_add_command, "write fast deny-oom", 1, 1, 1, "fast write bloom"],
+        ["BF.MADD", bloom_madd_command, "write fast deny-oom", 1, 1, 1, "fast write bloom"],
+        ["BF.EXISTS", bloom_exists_command, "readonly fast", 1, 1, 1, "fast read bloom"],
+        ["BF.MEXISTS", bloom_mexists_command, "readonly fast", 1, 1, 1, "fast read bloom"],
+        ["BF.MKEYEXISTS", bloom_mkeyexists_command, "readonly fast", 2, -1, 1, "fast read bloom"],
+        ["BF.CARD", bloom_card_command, "readonly", 1, 1, 1, "read bloom"],
+        ["BF.INTERCARD", bloom_intercard_command, "readonly", 1, 2, 1, "read bloom"],
+        ["BF.RESERVE", bloom_reserve_command, "write fast deny-oom", 1, 1, 1, "fast write bloom"],
+        ["BF.INFO", bloom_info_command, "readonly", 1, 1, 1, "read bloom"],
+        ["BF.INSERT", bloom_insert_command, "write fast deny-oom", 1, 1, 1, "fast write bloom"],
+        ["BF.CLEAR", bloom_clear_command, "write", 1, 1, 1, "write bloom"],
+        ["BF.COMPACT", bloom_compact_command, "write deny-oom", 1, 2, 1, "write bloom"],
+        ["BF.SWAP", bloom_swap_command, "write", 1, 2, 1, "write bloom"],
+        ["BF.MERGE", bloom_merge_command, "write deny-oom", 1, -1, 1, "write bloom"],
+        ["BF.SCANDUMP", bloom_scandump_command, "readonly", 1, 1, 1, "read bloom"],
+        ["BF.LOADCHUNK", bloom_loadchunk_command, "write deny-oom", 1, 1, 1, "write bloom"],
     ],
     configurations: [
         i64: [
             ["bloom-max-item-size", &*configs::BLOOM_CAPACITY, configs::BLOOM_CAPACITY_DEFAULT, configs::BLOOM_CAPACITY_MIN as i64, configs::BLOOM_CAPACITY_MAX as i64, ConfigurationFlags::DEFAULT, None],
             ["bloom-expansion-rate", &*configs::BLOOM_EXPANSION, configs::BLOOM_EXPANSION_DEFAULT, configs::BLOOM_EXPANSION_MIN as i64, configs::BLOOM_EXPANSION_MAX as i64, ConfigurationFlags::DEFAULT, None],
+            ["bloom-max-filters-per-object", &*configs::BLOOM_MAX_FILTERS_PER_OBJ, configs::BLOOM_MAX_FILTERS_PER_OBJ_DEFAULT, configs::BLOOM_MAX_FILTERS_PER_OBJ_MIN as i64, configs::BLOOM_MAX_FILTERS_PER_OBJ_MAX as i64, ConfigurationFlags::DEFAULT, None],
+            ["bloom-memory-usage-limit", &*configs::BLOOM_MEMORY_LIMIT_PER_OBJ, configs::BLOOM_MEMORY_LIMIT_PER_OBJ_DEFAULT, configs::BLOOM_MEMORY_LIMIT_PER_OBJ_MIN, configs::BLOOM_MEMORY_LIMIT_PER_OBJ_MAX, ConfigurationFlags::DEFAULT, None],
         ],
         string: [
         ],
         bool: [
         ],
         enum: [
+            ["bloom-hash-function", &*configs::BLOOM_HASH_FUNCTION, configs::BloomHashFunctionConfig::siphash, ConfigurationFlags::DEFAULT, None],
         ],
         module_args_as_configuration: true,
     ]
@@ -0,0 +1,36 @@
+use crate::MODULE_NAME;
+use std::sync::atomic::Ordering;
+use valkey_bloom_core::metrics::{
+    BLOOM_NUM_FILTERS_ACROSS_OBJECTS, BLOOM_NUM_OBJECTS, BLOOM_OBJECT_TOTAL_MEMORY_BYTES,
+};
+use valkey_module::{logging, InfoContext, ValkeyResult};
+
+/// Add the bloom metrics to the INFO command reply.
+fn add_bloom_info_section(ctx: &InfoContext) -> ValkeyResult<()> {
+    ctx.builder()
+        .add_section("bloom_core_metrics")
+        .field(
+            "bloom_total_memory_bytes",
+            BLOOM_OBJECT_TOTAL_MEMORY_BYTES.load(Ordering::Relaxed),
+        )?
+        .field(
+            "bloom_num_objects",
+            BLOOM_NUM_OBJECTS.load(Ordering::Relaxed),
+        )?
+        .field(
+            "bloom_num_filters_across_objects",
+            BLOOM_NUM_FILTERS_ACROSS_OBJECTS.load(Ordering::Relaxed),
+        )?
+        .build_section()?
+        .build_info()?;
+    Ok(())
+}
+
+/// Handler of the module's INFO sections.
+pub fn bloom_info_handler(ctx: &InfoContext, _for_crash_report: bool) {
+    if let Err(err) = add_bloom_info_section(ctx) {
+        logging::log_warning(
+            format!("{}: Failed to add the INFO section: {}", MODULE_NAME, err).as_str(),
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use crate::bloom;
-use crate::bloom::data_type::ValkeyDataType;
+use crate::bloom::data_type::{ModuleRdbIo, ValkeyDataType};
 use crate::bloom::utils::BloomFilterType;
-use std::os::raw::{c_char, c_int, c_void};
+use std::os::raw::{c_char, c_int, c_longlong, c_void};
 use std::ptr::null_mut;
+use valkey_bloom_core::rdb;
 use valkey_module::raw;
 use valkey_module::{RedisModuleDefragCtx, RedisModuleString};
 
@@ -13,26 +14,7 @@ use valkey_module::{RedisModuleDefragCtx, RedisModuleString};
 /// # Safety
 pub unsafe extern "C" fn bloom_rdb_save(rdb: *mut raw::RedisModuleIO, value: *mut c_void) {
     let v = &*value.cast::<BloomFilterType>();
-    raw::save_unsigned(rdb, v.filters.len() as u64);
-    raw::save_unsigned(rdb, v.expansion as u64);
-    raw::save_float(rdb, v.fp_rate);
-    let filter_list = &v.filters;
-    let mut filter_list_iter = filter_list.iter().peekable();
-    while let Some(filter) = filter_list_iter.next() {
-        let bloom = &filter.bloom;
-        let bitmap = bloom.bitmap();
-        raw::RedisModule_SaveStringBuffer.unwrap()(
-            rdb,
-            bitmap.as_ptr().cast::<c_char>(),
-            bitmap.len(),
-        );
-        raw::save_unsigned(rdb, bloom.number_of_bits());
-        raw::save_unsigned(rdb, bloom.number_of_hash_functions() as u64);
-        raw::save_unsigned(rdb, filter.capacity as u64);
-        if filter_list_iter.peek().is_none() {
-            raw::save_unsigned(rdb, filter.num_items as u64);
-        }
-    }
+    rdb::save_bloom_object(&mut ModuleRdbIo(rdb), v);
 }
 
 /// # Safety
@@ -48,14 +30,61 @@ pub unsafe extern "C" fn bloom_rdb_load(
     }
 }
 
+/// # Safety
+pub unsafe extern "C" fn redisbloom_rdb_save(rdb: *mut raw::RedisModuleIO, value: *mut c_void) {
+    let v = &*value.cast::<BloomFilterType>();
+    rdb::save_redisbloom_object(&mut ModuleRdbIo(rdb), v);
+}
+
+/// # Safety
+pub unsafe extern "C" fn redisbloom_rdb_load(
+    rdb: *mut raw::RedisModuleIO,
+    encver: c_int,
+) -> *mut c_void {
+    if let Some(item) = <BloomFilterType as ValkeyDataType>::load_from_redisbloom_rdb(rdb, encver) {
+        let bb = Box::new(item);
+        Box::into_raw(bb).cast::<libc::c_void>()
+    } else {
+        null_mut()
+    }
+}
+
+/// # Safety
+/// Rewrite a bloom item (AOF rewrite, or the slot snapshot of an atomic slot migration) as BF.LOADCHUNK commands of
+/// its header and of the chunks of its bitmaps, so that large items are not serialized into a single command.
+pub unsafe extern "C" fn bloom_aof_rewrite(
+    aof: *mut raw::RedisModuleIO,
+    key: *mut RedisModuleString,
+    value: *mut c_void,
+) {
+    let v = &*value.cast::<BloomFilterType>();
+    for (iterator, data) in v.load_chunks() {
+        raw::RedisModule_EmitAOF.unwrap()(
+            aof,
+            b"BF.LOADCHUNK\0".as_ptr().cast::<c_char>(),
+            b"slb\0".as_ptr().cast::<c_char>(),
+            key,
+            iterator as c_longlong,
+            data.as_ptr().cast::<c_char>(),
+            data.len(),
+        );
+    }
+}
+
 /// # Safety
 /// Load auxiliary data from RDB
 pub unsafe extern "C" fn bloom_aux_load(
     rdb: *mut raw::RedisModuleIO,
     _encver: c_int,
-    _when: c_int,
+    when: c_int,
 ) -> c_int {
-    bloom::data_type::bloom_rdb_aux_load(rdb)
+    bloom::data_type::bloom_rdb_aux_load(rdb, when)
+}
+
+/// # Safety
+/// Save auxiliary data to RDB
+pub unsafe extern "C" fn bloom_aux_save(rdb: *mut raw::RedisModuleIO, _when: c_int) {
+    bloom::data_type::bloom_rdb_aux_save(rdb);
 }
 
 /// # Safety
@@ -96,12 +125,16 @@ pub unsafe extern "C" fn bloom_free_effort(
 
 /// # Safety
 /// Raw handler for the Bloom object's defrag callback.
+/// Objects are defragmented by copying them, unless their free effort is too high to do it on the main thread.
 pub unsafe extern "C" fn bloom_defrag(
     _defrag_ctx: *mut RedisModuleDefragCtx,
     _from_key: *mut RedisModuleString,
     value: *mut *mut c_void,
 ) -> i32 {
     let curr_item = &*(*value).cast::<BloomFilterType>();
+    if !curr_item.should_defrag() {
+        return 0;
+    }
     let new_item = BloomFilterType::create_copy_from(curr_item);
     let bb = Box::new(new_item);
     drop(Box::from_raw((*value).cast::<BloomFilterType>()));
@@ -0,0 +1,500 @@
+use std::ffi::CString;
+use std::mem::MaybeUninit;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use valkey_module::commands::{get_redis_key_spec, BeginSearch, FindKeys, KeySpec, KeySpecFlags};
+use valkey_module::{raw, Context, Status, ValkeyError, ValkeyResult};
+
+// Note: methods in this mod register the metadata (arity, key specs, argument tree, since version and complexity)
+// of the BF.* commands with the server, which is reported by COMMAND DOCS and COMMAND INFO.
+
+const COMMAND_INFO_VERSION: raw::RedisModuleCommandInfoVersion =
+    raw::RedisModuleCommandInfoVersion {
+        version: 1,
+        sizeof_historyentry: std::mem::size_of::<raw::RedisModuleCommandHistoryEntry>(),
+        sizeof_keyspec: std::mem::size_of::<raw::RedisModuleCommandKeySpec>(),
+        sizeof_arg: std::mem::size_of::<raw::RedisModuleCommandArg>(),
+    };
+
+/// Module version in which the BF.* commands were introduced.
+const BLOOM_COMMANDS_SINCE: &str = "1.0.0";
+
+/// An argument of a BF.* command as described by COMMAND DOCS.
+struct CommandArg {
+    name: &'static str,
+    arg_type: raw::RedisModuleCommandArgType,
+    key_spec_index: c_int,
+    token: Option<&'static str>,
+    flags: u32,
+    subargs: Vec<CommandArg>,
+}
+
+impl CommandArg {
+    fn new(name: &'static str, arg_type: raw::RedisModuleCommandArgType) -> CommandArg {
+        CommandArg {
+            name,
+            arg_type,
+            key_spec_index: -1,
+            token: None,
+            flags: raw::REDISMODULE_CMD_ARG_NONE,
+            subargs: Vec::new(),
+        }
+    }
+
+    fn key(name: &'static str, key_spec_index: c_int) -> CommandArg {
+        let mut arg = CommandArg::new(
+            name,
+            raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_KEY,
+        );
+        arg.key_spec_index = key_spec_index;
+        arg
+    }
+
+    fn string(name: &'static str) -> CommandArg {
+        CommandArg::new(
+            name,
+            raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_STRING,
+        )
+    }
+
+    fn integer(name: &'static str) -> CommandArg {
+        CommandArg::new(
+            name,
+            raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_INTEGER,
+        )
+    }
+
+    fn double(name: &'static str) -> CommandArg {
+        CommandArg::new(
+            name,
+            raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_DOUBLE,
+        )
+    }
+
+    fn pure_token(name: &'static str, token: &'static str) -> CommandArg {
+        CommandArg::new(
+            name,
+            raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_PURE_TOKEN,
+        )
+        .token(token)
+    }
+
+    fn one_of(name: &'static str, subargs: Vec<CommandArg>) -> CommandArg {
+        let mut arg = CommandArg::new(
+            name,
+            raw::RedisModuleCommandArgType_REDISMODULE_ARG_TYPE_ONEOF,
+        );
+        arg.subargs = subargs;
+        arg
+    }
+
+    fn token(mut self, token: &'static str) -> CommandArg {
+        self.token = Some(token);
+        self
+    }
+
+    fn optional(mut self) -> CommandArg {
+        self.flags |= raw::REDISMODULE_CMD_ARG_OPTIONAL;
+        self
+    }
+
+    fn multiple(mut self) -> CommandArg {
+        self.flags |= raw::REDISMODULE_CMD_ARG_MULTIPLE;
+        self
+    }
+}
+
+/// Metadata of a BF.* command which is set on the command after it has been created.
+struct BloomCommandInfo {
+    name: &'static str,
+    summary: &'static str,
+    complexity: &'static str,
+    arity: c_int,
+    key_specs: Vec<KeySpec>,
+    args: Vec<CommandArg>,
+}
+
+/// Key spec of a single key found at the index of the command arguments.
+fn key_spec(index: i32, flags: KeySpecFlags) -> KeySpec {
+    KeySpec::new(
+        None,
+        flags,
+        BeginSearch::new_index(index),
+        FindKeys::new_range(0, 1, 0),
+    )
+}
+
+fn hash_function_arg() -> CommandArg {
+    CommandArg::one_of(
+        "hash_function",
+        vec![
+            CommandArg::pure_token("siphash", "SIPHASH"),
+            CommandArg::pure_token("xxhash3", "XXHASH3"),
+            CommandArg::pure_token("murmurhash3", "MURMURHASH3"),
+            CommandArg::pure_token("murmurhash64a", "MURMURHASH64A"),
+            CommandArg::pure_token("murmurhash2", "MURMURHASH2"),
+        ],
+    )
+    .token("HASH")
+    .optional()
+}
+
+/// The optional properties of the bloom object created by BF.ADD, BF.MADD and BF.INSERT when the key does not exist.
+fn create_option_args() -> Vec<CommandArg> {
+    vec![
+        CommandArg::double("error_rate").token("ERROR").optional(),
+        CommandArg::integer("capacity").token("CAPACITY").optional(),
+        CommandArg::integer("expansion")
+            .token("EXPANSION")
+            .optional(),
+        CommandArg::pure_token("nocreate", "NOCREATE").optional(),
+        CommandArg::pure_token("nonscaling", "NONSCALING").optional(),
+        hash_function_arg(),
+    ]
+}
+
+/// How BF.MADD and BF.INSERT handle items which cannot be added.
+fn add_mode_arg() -> CommandArg {
+    CommandArg::one_of(
+        "mode",
+        vec![
+            CommandArg::pure_token("atomic", "ATOMIC"),
+            CommandArg::pure_token("continue", "CONTINUE"),
+        ],
+    )
+    .optional()
+}
+
+fn bloom_command_infos() -> Vec<BloomCommandInfo> {
+    let insert_flags = KeySpecFlags::READ_WRITE | KeySpecFlags::INSERT;
+    vec![
+        BloomCommandInfo {
+            name: "BF.ADD",
+            summary: "Adds an item to a bloom object, creating it with the provided properties if it does not exist.",
+            complexity: "O(k), where k is the number of hash functions used by the last filter.",
+            arity: -3,
+            key_specs: vec![key_spec(1, insert_flags)],
+            args: [CommandArg::key("key", 0)]
+                .into_iter()
+                .chain(create_option_args())
+                .chain([CommandArg::string("item")])
+                .collect(),
+        },
+        BloomCommandInfo {
+            name: "BF.MADD",
+            summary: "Adds one or more items to a bloom object, creating it with the provided properties if it does not exist.",
+            complexity: "O(n * k), where n is the number of items and k is the number of hash functions.",
+            arity: -3,
+            key_specs: vec![key_spec(1, insert_flags)],
+            args: [CommandArg::key("key", 0)]
+                .into_iter()
+                .chain(create_option_args())
+                .chain([
+                    add_mode_arg(),
+                    CommandArg::pure_token("items", "ITEMS").optional(),
+                    CommandArg::string("item").multiple(),
+                ])
+                .collect(),
+        },
+        BloomCommandInfo {
+            name: "BF.EXISTS",
+            summary: "Determines whether an item may exist in a bloom object. With STRICT, a missing bloom object is an error.",
+            complexity: "O(k * f), where k is the number of hash functions and f is the number of filters.",
+            arity: -3,
+            key_specs: vec![key_spec(1, KeySpecFlags::READ_ONLY)],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::string("item"),
+                CommandArg::pure_token("strict", "STRICT").optional(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.MEXISTS",
+            summary: "Determines whether one or more items may exist in a bloom object.",
+            complexity: "O(n * k * f), where n is the number of items, k is the number of hash functions and f is the number of filters.",
+            arity: -3,
+            key_specs: vec![key_spec(1, KeySpecFlags::READ_ONLY)],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::string("item").multiple(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.MKEYEXISTS",
+            summary: "Determines whether an item may exist in each of one or more bloom objects.",
+            complexity: "O(n * k * f), where n is the number of keys, k is the number of hash functions and f is the number of filters.",
+            arity: -3,
+            key_specs: vec![KeySpec::new(
+                None,
+                KeySpecFlags::READ_ONLY,
+                BeginSearch::new_index(2),
+                FindKeys::new_range(-1, 1, 0),
+            )],
+            args: vec![
+                CommandArg::string("item"),
+                CommandArg::key("key", 0).multiple(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.CARD",
+            summary: "Returns the number of unique items added to a bloom object, or an estimate based on its set bits.",
+            complexity: "O(1) or O(m) with ESTIMATE, where m is the number of bits of all the filters.",
+            arity: -2,
+            key_specs: vec![key_spec(1, KeySpecFlags::READ_ONLY)],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::pure_token("estimate", "ESTIMATE").optional(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.INTERCARD",
+            summary: "Returns an estimate of the number of unique items added to both of two bloom objects.",
+            complexity: "O(m * f), where m is the number of bits of a filter and f is the number of filters.",
+            arity: 3,
+            key_specs: vec![
+                key_spec(1, KeySpecFlags::READ_ONLY),
+                key_spec(2, KeySpecFlags::READ_ONLY),
+            ],
+            args: vec![CommandArg::key("key1", 0), CommandArg::key("key2", 1)],
+        },
+        BloomCommandInfo {
+            name: "BF.RESERVE",
+            summary: "Creates an empty bloom object with the provided false positive rate and capacity.",
+            complexity: "O(1)",
+            arity: -4,
+            key_specs: vec![key_spec(1, KeySpecFlags::OVERWRITE | KeySpecFlags::INSERT)],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::double("error_rate"),
+                CommandArg::integer("capacity"),
+                CommandArg::one_of(
+                    "scaling",
+                    vec![
+                        CommandArg::integer("expansion").token("EXPANSION"),
+                        CommandArg::pure_token("nonscaling", "NONSCALING"),
+                    ],
+                )
+                .optional(),
+                CommandArg::one_of(
+                    "layout",
+                    vec![
+                        CommandArg::pure_token("standard", "STANDARD"),
+                        CommandArg::pure_token("blocked", "BLOCKED"),
+                        CommandArg::pure_token("redisbloom", "REDISBLOOM"),
+                    ],
+                )
+                .token("LAYOUT")
+                .optional(),
+                hash_function_arg(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.INFO",
+            summary: "Returns information about a bloom object.",
+            complexity: "O(1), or O(m) for the ESTIMATE field, where m is the number of bits of all the filters.",
+            arity: -2,
+            key_specs: vec![key_spec(1, KeySpecFlags::READ_ONLY)],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::one_of(
+                    "info",
+                    vec![
+                        CommandArg::pure_token("capacity", "CAPACITY"),
+                        CommandArg::pure_token("size", "SIZE"),
+                        CommandArg::pure_token("filters", "FILTERS"),
+                        CommandArg::pure_token("items", "ITEMS"),
+                        CommandArg::pure_token("estimate", "ESTIMATE"),
+                        CommandArg::pure_token("expansion", "EXPANSION"),
+                        CommandArg::pure_token("layout", "LAYOUT"),
+                        CommandArg::pure_token("hash", "HASH"),
+                        CommandArg::pure_token("maxfilters", "MAXFILTERS"),
+                    ],
+                )
+                .optional(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.INSERT",
+            summary: "Adds one or more items to a bloom object, creating it with the provided properties if it does not exist.",
+            complexity: "O(n * k), where n is the number of items and k is the number of hash functions.",
+            arity: -2,
+            key_specs: vec![key_spec(1, insert_flags)],
+            args: [CommandArg::key("key", 0)]
+                .into_iter()
+                .chain(create_option_args())
+                .chain([
+                    add_mode_arg(),
+                    CommandArg::string("item").token("ITEMS").multiple(),
+                ])
+                .collect(),
+        },
+        BloomCommandInfo {
+            name: "BF.CLEAR",
+            summary: "Removes all items from a bloom object while keeping its properties.",
+            complexity: "O(m), where m is the number of bits of the first filter.",
+            arity: 2,
+            key_specs: vec![key_spec(1, KeySpecFlags::READ_WRITE | KeySpecFlags::DELETE)],
+            args: vec![CommandArg::key("key", 0)],
+        },
+        BloomCommandInfo {
+            name: "BF.COMPACT",
+            summary: "Creates an empty bloom object with a single filter, sized for the items of an existing bloom object and with its properties, to rebuild it into.",
+            complexity: "O(m), where m is the number of bits of the created filter.",
+            arity: -3,
+            key_specs: vec![
+                key_spec(1, KeySpecFlags::READ_ONLY),
+                key_spec(2, KeySpecFlags::OVERWRITE | KeySpecFlags::INSERT),
+            ],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::key("temp_key", 1),
+                CommandArg::integer("capacity").token("CAPACITY").optional(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.SWAP",
+            summary: "Replaces a bloom object with the bloom object rebuilt at a temporary key, which is deleted.",
+            complexity: "O(1)",
+            arity: 3,
+            key_specs: vec![
+                key_spec(1, KeySpecFlags::OVERWRITE | KeySpecFlags::UPDATE),
+                key_spec(
+                    2,
+                    KeySpecFlags::READ_WRITE | KeySpecFlags::ACCESS | KeySpecFlags::DELETE,
+                ),
+            ],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::key("temp_key", 1),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.MERGE",
+            summary: "Merges the items of one or more bloom objects with the same layout, hash function and filter sizes into a destination bloom object, which is created as a copy of the first source if it does not exist.",
+            complexity: "O(n * m), where n is the number of source keys and m is the number of bits of all the filters.",
+            arity: -3,
+            key_specs: vec![
+                key_spec(1, insert_flags | KeySpecFlags::UPDATE),
+                KeySpec::new(
+                    None,
+                    KeySpecFlags::READ_ONLY | KeySpecFlags::ACCESS,
+                    BeginSearch::new_index(2),
+                    FindKeys::new_range(-1, 1, 0),
+                ),
+            ],
+            args: vec![
+                CommandArg::key("destkey", 0),
+                CommandArg::key("sourcekey", 1).multiple(),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.SCANDUMP",
+            summary: "Returns the header of a bloom object, then the chunks of its bitmaps, in the format of RedisBloom.",
+            complexity: "O(m), where m is the number of bits of the filter of the chunk.",
+            arity: 3,
+            key_specs: vec![key_spec(1, KeySpecFlags::READ_ONLY)],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::integer("iterator"),
+            ],
+        },
+        BloomCommandInfo {
+            name: "BF.LOADCHUNK",
+            summary: "Restores a bloom object from the header and chunks returned by BF.SCANDUMP.",
+            complexity: "O(m), where m is the number of bits of the filter of the chunk.",
+            arity: 4,
+            key_specs: vec![key_spec(1, insert_flags | KeySpecFlags::UPDATE)],
+            args: vec![
+                CommandArg::key("key", 0),
+                CommandArg::integer("iterator"),
+                CommandArg::string("data"),
+            ],
+        },
+    ]
+}
+
+/// Owns the C strings and argument arrays referenced by a raw command info until it has been copied by the server.
+#[derive(Default)]
+struct RawCommandInfoStorage {
+    strings: Vec<CString>,
+    args: Vec<Vec<raw::RedisModuleCommandArg>>,
+}
+
+impl RawCommandInfoStorage {
+    fn c_str(&mut self, value: &str) -> *const c_char {
+        let value = CString::new(value).unwrap();
+        let value_ptr = value.as_ptr();
+        self.strings.push(value);
+        value_ptr
+    }
+
+    /// Converts the arguments into a zero terminated array of raw arguments.
+    fn raw_args(&mut self, args: &[CommandArg]) -> *mut raw::RedisModuleCommandArg {
+        if args.is_empty() {
+            return ptr::null_mut();
+        }
+        let mut raw_args = Vec::with_capacity(args.len() + 1);
+        for arg in args {
+            raw_args.push(raw::RedisModuleCommandArg {
+                name: self.c_str(arg.name),
+                type_: arg.arg_type,
+                key_spec_index: arg.key_spec_index,
+                token: arg.token.map_or(ptr::null(), |token| self.c_str(token)),
+                summary: ptr::null(),
+                since: ptr::null(),
+                flags: arg.flags as c_int,
+                deprecated_since: ptr::null(),
+                subargs: self.raw_args(&arg.subargs),
+                display_text: ptr::null(),
+            });
+        }
+        raw_args.push(unsafe { MaybeUninit::zeroed().assume_init() });
+        let raw_args_ptr = raw_args.as_mut_ptr();
+        self.args.push(raw_args);
+        raw_args_ptr
+    }
+}
+
+fn set_bloom_command_info(ctx: &Context, info: BloomCommandInfo) -> ValkeyResult<()> {
+    let name = CString::new(info.name).unwrap();
+    let command = unsafe { raw::RedisModule_GetCommand.unwrap()(ctx.ctx, name.as_ptr()) };
+    if command.is_null() {
+        return Err(ValkeyError::String(format!(
+            "Failed finding command {} to set its info.",
+            info.name
+        )));
+    }
+    let mut storage = RawCommandInfoStorage::default();
+    let mut key_specs = get_redis_key_spec(info.key_specs);
+    let mut command_info = raw::RedisModuleCommandInfo {
+        version: &COMMAND_INFO_VERSION,
+        summary: storage.c_str(info.summary),
+        complexity: storage.c_str(info.complexity),
+        since: storage.c_str(BLOOM_COMMANDS_SINCE),
+        history: ptr::null_mut(),
+        tips: ptr::null(),
+        arity: info.arity,
+        key_specs: key_specs.as_mut_ptr(),
+        args: storage.raw_args(&info.args),
+    };
+    if unsafe { raw::RedisModule_SetCommandInfo.unwrap()(command, &mut command_info) }
+        == raw::Status::Err as c_int
+    {
+        return Err(ValkeyError::String(format!(
+            "Failed setting info for command {}.",
+            info.name
+        )));
+    }
+    Ok(())
+}
+
+/// Sets the command info of all the BF.* commands. Must be called during module load after the commands are created.
+pub fn set_bloom_commands_info(ctx: &Context) -> Status {
+    for info in bloom_command_infos() {
+        if let Err(err) = set_bloom_command_info(ctx, info) {
+            ctx.log_warning(&err.to_string());
+            return Status::Err;
+        }
+    }
+    Status::Ok
+}
@@ -1 +1,2 @@
 pub mod bloom_callback;
+pub mod command_info;
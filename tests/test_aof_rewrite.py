@@ -0,0 +1,105 @@
+import os
+import shutil
+import pytest
+from valkey import ResponseError
+from valkey_bloom_test_case import ValkeyBloomTestCaseBase
+from valkeytests.util.waiters import wait_for_equal
+from valkeytests.conftest import resource_port_tracker
+
+class TestBloomAofRewrite(ValkeyBloomTestCaseBase):
+
+    def get_custom_args(self):
+        args = super(TestBloomAofRewrite, self).get_custom_args()
+        # Keys are rewritten as commands rather than into an RDB preamble, in a directory of this server.
+        args.update({
+            'appendonly': 'yes',
+            'aof-use-rdb-preamble': 'no',
+            'appenddirname': 'appendonlydir_{}'.format(self.port),
+        })
+        return args
+
+    def teardown(self):
+        super(TestBloomAofRewrite, self).teardown()
+        shutil.rmtree(os.path.join(self.testdir, 'appendonlydir_{}'.format(self.port)), ignore_errors=True)
+
+    def scandump(self, key):
+        """ Returns the iterators and data of the BF.LOADCHUNK commands which load the key, from a BF.SCANDUMP iteration.
+        """
+        chunks = []
+        iterator = 0
+        while True:
+            iterator, data = self.client.execute_command(f'BF.SCANDUMP {key} {iterator}')
+            if iterator == 0:
+                return chunks
+            chunks.append((iterator, data))
+
+    def wait_for_aof_rewrite(self):
+        persistence_info = lambda: self.client.info('persistence')
+        wait_for_equal(lambda: persistence_info()['aof_rewrite_in_progress'] + persistence_info()['aof_rewrite_scheduled'], 0)
+        assert persistence_info()['aof_last_bgrewrite_status'] == 'ok'
+
+    def base_aof_path(self):
+        aof_dir = os.path.join(self.testdir, 'appendonlydir_{}'.format(self.port))
+        with open(os.path.join(aof_dir, 'appendonly.aof.manifest')) as manifest:
+            for line in manifest:
+                fields = line.split()
+                entry = dict(zip(fields[::2], fields[1::2]))
+                if entry.get('type') == 'b':
+                    return os.path.join(aof_dir, entry['file'])
+        assert False, "The manifest has no base AOF file"
+
+    def test_aof_rewrite_in_chunks(self):
+        create_cmds = [
+            'BF.RESERVE large 0.001 10000000',
+            'BF.RESERVE blocked 0.01 1000 EXPANSION 4 LAYOUT BLOCKED HASH XXHASH3',
+            'BF.RESERVE redisbloom 0.01 1000 LAYOUT REDISBLOOM HASH MURMURHASH64A',
+            'BF.RESERVE nonscaling 0.01 1000 NONSCALING HASH MURMURHASH3',
+        ]
+        items = ' '.join('item' + str(i) for i in range(3000))
+        for cmd in create_cmds:
+            assert self.client.execute_command(cmd) == b'OK'
+            self.client.execute_command(f'BF.MADD {cmd.split()[1]} {items}')
+        assert self.client.execute_command('EXPIRE blocked 1000') == 1
+        keys = ['large', 'blocked', 'redisbloom', 'nonscaling']
+        dumps = {key: self.scandump(key) for key in keys}
+        infos = {key: self.client.execute_command(f'BF.INFO {key}') for key in keys}
+        # The bitmap of the large object is split into several chunks.
+        assert len(dumps['large']) > 2
+        self.wait_for_aof_rewrite()
+        self.client.execute_command('BGREWRITEAOF')
+        self.wait_for_aof_rewrite()
+        # Every object is rewritten as the BF.LOADCHUNK commands of its header and chunks.
+        with open(self.base_aof_path(), 'rb') as aof:
+            assert aof.read().count(b'BF.LOADCHUNK') == sum(len(chunks) for chunks in dumps.values())
+        # The objects are loaded back from the rewritten commands.
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        for key in keys:
+            assert self.scandump(key) == dumps[key]
+            assert self.client.execute_command(f'BF.INFO {key}') == infos[key]
+            assert self.client.execute_command(f'BF.MEXISTS {key} item0 item999') == [1, 1]
+        assert self.client.execute_command('TTL blocked') > 0
+        assert self.client.execute_command('TTL large') == -1
+
+    def test_aof_rewrite_above_memory_limit(self):
+        # Objects larger than the memory limit, such as ones created before the limit was lowered, are loaded back from
+        # the rewritten commands instead of being dropped.
+        assert self.client.execute_command('BF.RESERVE large 0.001 10000000') == b'OK'
+        assert self.client.execute_command('BF.MADD large item1 item2') == [1, 1]
+        dump = self.scandump('large')
+        limit = 1000000
+        assert self.client.execute_command('BF.INFO large SIZE') > limit
+        assert self.client.execute_command(f'CONFIG SET bloom-memory-usage-limit {limit}') == b'OK'
+        self.wait_for_aof_rewrite()
+        self.client.execute_command('BGREWRITEAOF')
+        self.wait_for_aof_rewrite()
+        self.server.args['bloom-memory-usage-limit'] = str(limit)
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        assert self.client.execute_command('CONFIG GET bloom-memory-usage-limit') == [b'bloom-memory-usage-limit', str(limit).encode()]
+        assert self.scandump('large') == dump
+        assert self.client.execute_command('BF.MEXISTS large item1 item2') == [1, 1]
+        # Clients are still limited when they load an object of the same size.
+        with pytest.raises(ResponseError, match='operation exceeds bloom object memory limit'):
+            self.client.execute_command('BF.LOADCHUNK', 'copy', dump[0][0], dump[0][1])
+        assert self.client.execute_command('EXISTS copy') == 0
@@ -23,7 +23,7 @@ class TestBloomBasic(ValkeyBloomTestCaseBase):
         assert(module_loaded)
         # Validate that all the BF.* commands are supported on the server.
         command_cmd_result = client.execute_command('COMMAND')
-        bf_cmds = ["BF.ADD", "BF.EXISTS", "BF.MADD", "BF.MEXISTS", "BF.INFO", "BF.CARD", "BF.RESERVE", "BF.INSERT"]
+        bf_cmds = ["BF.ADD", "BF.EXISTS", "BF.MADD", "BF.MEXISTS", "BF.MKEYEXISTS", "BF.INFO", "BF.CARD", "BF.INTERCARD", "BF.RESERVE", "BF.INSERT", "BF.CLEAR", "BF.COMPACT", "BF.SWAP", "BF.MERGE", "BF.SCANDUMP", "BF.LOADCHUNK"]
         assert all(item in command_cmd_result for item in bf_cmds)
         # Basic bloom filter create, item add and item exists validation.
         bf_add_result = client.execute_command('BF.ADD filter1 item1')
@@ -171,6 +171,31 @@ class TestBloomBasic(ValkeyBloomTestCaseBase):
         self.verify_bloom_filter_item_existence(client, 'B', 'ITEMB', should_exist=False)
         self.verify_server_key_count(client, 0)
 
+    def test_bloom_metrics_and_lazyfree(self):
+        client = self.server.get_new_client()
+        def bloom_metrics():
+            info = client.info('bf')
+            return (info['bf_bloom_num_objects'], info['bf_bloom_num_filters_across_objects'], info['bf_bloom_total_memory_bytes'])
+        assert bloom_metrics() == (0, 0, 0)
+        assert client.execute_command('BF.RESERVE small 0.001 1000') == b'OK'
+        assert client.execute_command('BF.INSERT scaled CAPACITY 1 ITEMS item1 item2') == [1, 1]
+        total_size = client.execute_command('BF.INFO small SIZE') + client.execute_command('BF.INFO scaled SIZE')
+        assert bloom_metrics() == (2, 3, total_size)
+        assert client.execute_command('COPY scaled copied') == 1
+        assert bloom_metrics() == (3, 5, total_size + client.execute_command('BF.INFO scaled SIZE'))
+        assert client.execute_command('DEL copied') == 1
+        assert bloom_metrics() == (2, 3, total_size)
+        # Large objects exceed the lazyfree threshold through their bitmap size and are freed in a background thread on UNLINK.
+        lazyfreed_objects = client.info('stats')['lazyfreed_objects']
+        assert client.execute_command('BF.RESERVE large 0.001 10000000') == b'OK'
+        assert client.execute_command('UNLINK small') == 1
+        assert client.info('stats')['lazyfreed_objects'] == lazyfreed_objects
+        assert client.execute_command('UNLINK large') == 1
+        wait_for_equal(lambda: client.info('stats')['lazyfreed_objects'], lazyfreed_objects + 1)
+        assert bloom_metrics() == (1, 2, client.execute_command('BF.INFO scaled SIZE'))
+        assert client.execute_command('FLUSHALL')
+        wait_for_equal(bloom_metrics, (0, 0, 0))
+
     def test_bloom_expiration(self):
         client = self.server.get_new_client()
         # expiration
@@ -0,0 +1,40 @@
+import pytest
+from valkey import ResponseError
+from valkey_bloom_test_case import ValkeyBloomTestCaseBase
+from valkeytests.conftest import resource_port_tracker
+
+class TestBloomACLCategory(ValkeyBloomTestCaseBase):
+
+    def test_bloom_acl_category(self):
+        bloom_commands = [b'BF.ADD', b'BF.MADD', b'BF.EXISTS', b'BF.MEXISTS', b'BF.MKEYEXISTS', b'BF.CARD', b'BF.INTERCARD', b'BF.RESERVE', b'BF.INFO', b'BF.INSERT', b'BF.CLEAR', b'BF.COMPACT', b'BF.SWAP', b'BF.MERGE', b'BF.SCANDUMP', b'BF.LOADCHUNK']
+        read_commands = [b'BF.EXISTS', b'BF.MEXISTS', b'BF.MKEYEXISTS', b'BF.CARD', b'BF.INTERCARD', b'BF.INFO', b'BF.SCANDUMP']
+        write_commands = [b'BF.ADD', b'BF.MADD', b'BF.RESERVE', b'BF.INSERT', b'BF.CLEAR', b'BF.COMPACT', b'BF.SWAP', b'BF.MERGE', b'BF.LOADCHUNK']
+        # Validate that the module defined ACL category is registered and contains every BF.* command.
+        assert b'bloom' in self.client.execute_command('ACL CAT')
+        assert sorted(self.client.execute_command('ACL CAT bloom')) == sorted(bloom_commands)
+        read_category = self.client.execute_command('ACL CAT read')
+        write_category = self.client.execute_command('ACL CAT write')
+        fast_category = self.client.execute_command('ACL CAT fast')
+        assert all(cmd in read_category and cmd not in write_category for cmd in read_commands)
+        assert all(cmd in write_category and cmd not in read_category for cmd in write_commands)
+        slow_commands = [b'BF.CARD', b'BF.INFO', b'BF.INTERCARD', b'BF.CLEAR', b'BF.COMPACT', b'BF.SWAP', b'BF.MERGE', b'BF.SCANDUMP', b'BF.LOADCHUNK']
+        assert all(cmd in fast_category for cmd in bloom_commands if cmd not in slow_commands)
+        assert all(cmd not in fast_category for cmd in slow_commands)
+
+    def test_bloom_acl_category_permissions(self):
+        assert self.client.execute_command('ACL SETUSER bloom_user on >bloom_pass ~* +@bloom') == b'OK'
+        assert self.client.execute_command('ACL SETUSER bloom_reader on >reader_pass ~* +@bloom -@write') == b'OK'
+        bloom_user = self.server.get_new_client()
+        assert bloom_user.execute_command('AUTH bloom_user bloom_pass') == b'OK'
+        assert bloom_user.execute_command('BF.ADD key item') == 1
+        assert bloom_user.execute_command('BF.RESERVE key1 0.01 1000') == b'OK'
+        assert bloom_user.execute_command('BF.EXISTS key item') == 1
+        assert bloom_user.execute_command('BF.MKEYEXISTS item key key1') == [1, 0]
+        with pytest.raises(ResponseError, match='NOPERM'):
+            bloom_user.execute_command('SET key2 value')
+        bloom_reader = self.server.get_new_client()
+        assert bloom_reader.execute_command('AUTH bloom_reader reader_pass') == b'OK'
+        assert bloom_reader.execute_command('BF.EXISTS key item') == 1
+        assert bloom_reader.execute_command('BF.CARD key') == 1
+        with pytest.raises(ResponseError, match='NOPERM'):
+            bloom_reader.execute_command('BF.ADD key item2')
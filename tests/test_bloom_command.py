@@ -1,4 +1,6 @@
 import pytest
+import struct
+from valkey import ResponseError
 from valkey_bloom_test_case import ValkeyBloomTestCaseBase
 from valkeytests.conftest import resource_port_tracker
 
@@ -10,14 +12,71 @@ class TestBloomCommand(ValkeyBloomTestCaseBase):
         assert actual_arity == expected_arity, f"Arity mismatch for command '{command}'"
 
     def test_bloom_command_arity(self):
-        self.verify_command_arity('BF.EXISTS', -1)
-        self.verify_command_arity('BF.ADD', -1)
-        self.verify_command_arity('BF.MEXISTS', -1)
-        self.verify_command_arity('BF.MADD', -1)
-        self.verify_command_arity('BF.CARD', -1)
-        self.verify_command_arity('BF.RESERVE', -1)
-        self.verify_command_arity('BF.INFO', -1)
-        self.verify_command_arity('BF.INSERT', -1)
+        self.verify_command_arity('BF.EXISTS', -3)
+        self.verify_command_arity('BF.ADD', -3)
+        self.verify_command_arity('BF.MEXISTS', -3)
+        self.verify_command_arity('BF.MADD', -3)
+        self.verify_command_arity('BF.MKEYEXISTS', -3)
+        self.verify_command_arity('BF.CARD', -2)
+        self.verify_command_arity('BF.INTERCARD', 3)
+        self.verify_command_arity('BF.RESERVE', -4)
+        self.verify_command_arity('BF.INFO', -2)
+        self.verify_command_arity('BF.INSERT', -2)
+        self.verify_command_arity('BF.CLEAR', 2)
+        self.verify_command_arity('BF.COMPACT', -3)
+        self.verify_command_arity('BF.SWAP', 3)
+        self.verify_command_arity('BF.MERGE', -3)
+        self.verify_command_arity('BF.SCANDUMP', 3)
+        self.verify_command_arity('BF.LOADCHUNK', 4)
+
+    def reply_to_dict(self, reply):
+        if isinstance(reply, dict):
+            return reply
+        return {reply[i]: reply[i + 1] for i in range(0, len(reply), 2)}
+
+    def test_bloom_command_docs(self):
+        bf_cmds = ['BF.ADD', 'BF.MADD', 'BF.EXISTS', 'BF.MEXISTS', 'BF.MKEYEXISTS', 'BF.CARD', 'BF.INTERCARD', 'BF.RESERVE', 'BF.INFO', 'BF.INSERT', 'BF.CLEAR', 'BF.COMPACT', 'BF.SWAP', 'BF.MERGE', 'BF.SCANDUMP', 'BF.LOADCHUNK']
+        for cmd in bf_cmds:
+            command_docs = self.reply_to_dict(self.client.execute_command('COMMAND', 'DOCS', cmd))
+            assert len(command_docs) == 1
+            docs = self.reply_to_dict(list(command_docs.values())[0])
+            assert docs[b'since'] == b'1.0.0'
+            assert docs[b'summary'] and docs[b'complexity']
+            assert docs[b'module'] == b'bf'
+            assert len(docs[b'arguments']) > 0
+        insert_docs = self.reply_to_dict(list(self.reply_to_dict(self.client.execute_command('COMMAND', 'DOCS', 'BF.INSERT')).values())[0])
+        insert_args = [self.reply_to_dict(arg) for arg in insert_docs[b'arguments']]
+        assert [arg[b'name'] for arg in insert_args] == [b'key', b'error_rate', b'capacity', b'expansion', b'nocreate', b'nonscaling', b'hash_function', b'mode', b'item']
+        assert [arg.get(b'token') for arg in insert_args] == [None, b'ERROR', b'CAPACITY', b'EXPANSION', b'NOCREATE', b'NONSCALING', b'HASH', None, b'ITEMS']
+        assert insert_args[0][b'type'] == b'key' and insert_args[0][b'key_spec_index'] == 0
+        assert b'optional' in insert_args[1][b'flags'] and b'multiple' in insert_args[8][b'flags']
+        hash_args = [self.reply_to_dict(arg) for arg in insert_args[6][b'arguments']]
+        add_docs = self.reply_to_dict(list(self.reply_to_dict(self.client.execute_command('COMMAND', 'DOCS', 'BF.ADD')).values())[0])
+        add_args = [self.reply_to_dict(arg) for arg in add_docs[b'arguments']]
+        assert [arg[b'name'] for arg in add_args] == [b'key', b'error_rate', b'capacity', b'expansion', b'nocreate', b'nonscaling', b'hash_function', b'item']
+        assert [arg[b'token'] for arg in hash_args] == [b'SIPHASH', b'XXHASH3', b'MURMURHASH3', b'MURMURHASH64A', b'MURMURHASH2']
+
+    def test_bloom_command_key_specs(self):
+        key_specs_test_cases = [
+            ('BF.ADD key item', [[b'key', [b'RW', b'insert']]]),
+            ('BF.MADD key item1 item2', [[b'key', [b'RW', b'insert']]]),
+            ('BF.INSERT key CAPACITY 100 ITEMS item1', [[b'key', [b'RW', b'insert']]]),
+            ('BF.RESERVE key 0.01 1000', [[b'key', [b'OW', b'insert']]]),
+            ('BF.EXISTS key item', [[b'key', [b'RO']]]),
+            ('BF.MEXISTS key item1 item2', [[b'key', [b'RO']]]),
+            ('BF.CARD key', [[b'key', [b'RO']]]),
+            ('BF.INTERCARD key1 key2', [[b'key1', [b'RO']], [b'key2', [b'RO']]]),
+            ('BF.INFO key CAPACITY', [[b'key', [b'RO']]]),
+            ('BF.MKEYEXISTS item key1 key2', [[b'key1', [b'RO']], [b'key2', [b'RO']]]),
+            ('BF.CLEAR key', [[b'key', [b'RW', b'delete']]]),
+            ('BF.COMPACT key temp CAPACITY 100', [[b'key', [b'RO']], [b'temp', [b'OW', b'insert']]]),
+            ('BF.SWAP key temp', [[b'key', [b'OW', b'update']], [b'temp', [b'RW', b'access', b'delete']]]),
+            ('BF.MERGE dest key1 key2', [[b'dest', [b'RW', b'update', b'insert']], [b'key1', [b'RO', b'access']], [b'key2', [b'RO', b'access']]]),
+            ('BF.SCANDUMP key 0', [[b'key', [b'RO']]]),
+            ('BF.LOADCHUNK key 1 data', [[b'key', [b'RW', b'update', b'insert']]]),
+        ]
+        for cmd, expected_keys in key_specs_test_cases:
+            assert self.client.execute_command('COMMAND GETKEYSANDFLAGS ' + cmd) == expected_keys
 
     def test_bloom_command_error(self):
         # test set up
@@ -31,6 +90,24 @@ class TestBloomCommand(ValkeyBloomTestCaseBase):
         basic_error_test_cases = [
             # not found
             ('BF.INFO TEST404', 'not found'),
+            ('BF.CLEAR TEST404', 'not found'),
+            ('BF.COMPACT TEST404 TEMP', 'not found'),
+            ('BF.COMPACT bf bf', 'item exists'),
+            ('BF.COMPACT bf key', 'item exists'),
+            ('BF.COMPACT bf TEMP CAPACITY 0', '(capacity should be larger than 0)'),
+            ('BF.COMPACT bf TEMP CAPACITY 9223372036854775808', 'bad capacity'),
+            ('BF.COMPACT bf TEMP ERROR 0.01', 'unknown argument received'),
+            ('BF.SWAP bf TEST404', 'not found'),
+            ('BF.SWAP TEST404 bf', 'not found'),
+            ('BF.MERGE bf TEST404', 'not found'),
+            ('BF.MERGE TEST404 TEST404', 'not found'),
+            ('BF.SCANDUMP TEST404 0', 'not found'),
+            ('BF.SCANDUMP bf -1', 'bad iterator'),
+            ('BF.LOADCHUNK TEST404 100 data', 'not found'),
+            ('BF.LOADCHUNK TEST404 0 data', 'bad iterator'),
+            ('BF.LOADCHUNK bf 1 data', 'item exists'),
+            ('BF.LOADCHUNK TEST404 1 data', 'received bad data'),
+            ('BF.LOADCHUNK bf 100000000 data', 'invalid offset - no link found'),
             # incorrect syntax and argument usage
             ('BF.ADD bf_non 2', 'non scaling filter is full'),
             ('bf.info key item', 'invalid information value'),
@@ -38,30 +115,61 @@ class TestBloomCommand(ValkeyBloomTestCaseBase):
             ('BF.INSERT KEY HELLO WORLD', 'unknown argument received'),
             ('BF.INSERT KEY error 2 ITEMS test1', '(0 < error rate range < 1)'),
             ('BF.INSERT TEST_LIMIT ERROR 0.999999999 ITEMS ERROR_RATE', '(0 < error rate range < 1)'),
-            ('BF.INSERT TEST_LIMIT CAPACITY 4394967295 ITEMS CAP', 'bad capacity'),
+            ('BF.INSERT TEST_LIMIT CAPACITY 9223372036854775808 ITEMS CAP', 'bad capacity'),
             ('BF.INSERT TEST_LIMIT CAPACITY 0 ITEMS CAP0', '(capacity should be larger than 0)'),
             ('BF.INSERT TEST_LIMIT EXPANSION 11 ITEMS EXPAN', 'bad expansion'),
             ('BF.INSERT TEST_NOCREATE NOCREATE ITEMS A B', 'not found'),
+            ('BF.ADD TEST_NOCREATE NOCREATE A', 'not found'),
+            ('BF.MADD TEST_NOCREATE NOCREATE ITEMS A B', 'not found'),
+            ('BF.ADD TEST_LIMIT ERROR 2 A', '(0 < error rate range < 1)'),
+            ('BF.ADD TEST_LIMIT CAPACITY 0 A', '(capacity should be larger than 0)'),
+            ('BF.MADD TEST_LIMIT EXPANSION 11 ITEMS A', 'bad expansion'),
+            ('BF.MADD TEST_LIMIT ATOMIC CONTINUE ITEMS A', 'ATOMIC and CONTINUE cannot be used together'),
+            ('BF.INSERT TEST_LIMIT CONTINUE CONTINUE ITEMS A', 'ATOMIC and CONTINUE cannot be used together'),
+            ('BF.ADD TEST_LIMIT ATOMIC A', 'unknown argument received'),
             ('BF.RESERVE KEY String 100', 'bad error rate'),
             ('BF.RESERVE KEY 0.999999999 3000', '(0 < error rate range < 1)'),
             ('BF.RESERVE KEY 2 100', '(0 < error rate range < 1)'),
             ('BF.RESERVE KEY 0.01 String', 'bad capacity'),
             ('BF.RESERVE KEY 0.01 0.01', 'bad capacity'),
             ('BF.RESERVE KEY 0.01 -1', 'bad capacity'),
-            ('BF.RESERVE KEY 0.01 4394967295', 'bad capacity'),
+            ('BF.RESERVE KEY 0.01 9223372036854775808', 'bad capacity'),
             ('BF.RESERVE bf 0.01 1000', 'item exists'),
             ('BF.RESERVE TEST_CAP 0.50 0', '(capacity should be larger than 0)'),
+            ('BF.RESERVE TEST_LAYOUT 0.01 1000 LAYOUT UNKNOWN', 'bad layout'),
+            ('BF.RESERVE TEST_HASH 0.01 1000 HASH UNKNOWN', 'bad hash function'),
+            ('BF.RESERVE TEST_UNKNOWN 0.01 1000 UNKNOWN', 'unknown argument received'),
+            ('BF.RESERVE TEST_MEMORY 0.01 9223372036854775807', 'operation exceeds bloom object memory limit'),
+            ('BF.ADD TEST_MEMORY CAPACITY 9223372036854775807 ITEM', 'operation exceeds bloom object memory limit'),
+            ('BF.INSERT TEST_MEMORY CAPACITY 9223372036854775807 ITEMS ITEM', 'operation exceeds bloom object memory limit'),
+            ('BF.COMPACT bf TEST_MEMORY CAPACITY 9223372036854775807', 'operation exceeds bloom object memory limit'),
+            ('BF.INSERT TEST_HASH HASH sha256 ITEMS item', 'bad hash function'),
 
             # wrong number of arguments
             ('BF.ADD TEST', 'wrong number of arguments for \'BF.ADD\' command'),
             ('BF.ADD', 'wrong number of arguments for \'BF.ADD\' command'),
-            ('BF.ADD HELLO TEST WORLD', 'wrong number of arguments for \'BF.ADD\' command'),
-            ('BF.CARD KEY ITEM', 'wrong number of arguments for \'BF.CARD\' command'),
+            ('BF.ADD HELLO TEST WORLD', 'unknown argument received'),
+            ('BF.ADD HELLO CAPACITY WORLD', 'wrong number of arguments for \'BF.ADD\' command'),
+            ('BF.MADD HELLO NOCREATE ITEMS', 'wrong number of arguments for \'BF.MADD\' command'),
+            ('BF.CARD KEY ITEM', 'unknown argument received'),
+            ('BF.CARD KEY ESTIMATE ITEM', 'wrong number of arguments for \'BF.CARD\' command'),
+            ('BF.INFO KEY ESTIMATE ITEM', 'wrong number of arguments for \'BF.INFO\' command'),
             ('bf.card', 'wrong number of arguments for \'BF.CARD\' command'),
             ('BF.EXISTS', 'wrong number of arguments for \'BF.EXISTS\' command'),
             ('bf.exists item', 'wrong number of arguments for \'BF.EXISTS\' command'),
-            ('bf.exists key item hello', 'wrong number of arguments for \'BF.EXISTS\' command'),
+            ('bf.exists key item hello', 'unknown argument received'),
+            ('bf.exists key item STRICT hello', 'wrong number of arguments for \'BF.EXISTS\' command'),
+            ('BF.EXISTS TEST404 item STRICT', 'not found'),
             ('BF.INFO', 'wrong number of arguments for \'BF.INFO\' command'),
+            ('BF.CLEAR', 'wrong number of arguments for \'BF.CLEAR\' command'),
+            ('BF.CLEAR key item', 'wrong number of arguments for \'BF.CLEAR\' command'),
+            ('BF.COMPACT key', 'wrong number of arguments for \'BF.COMPACT\' command'),
+            ('BF.COMPACT key temp CAPACITY', 'wrong number of arguments for \'BF.COMPACT\' command'),
+            ('BF.SWAP key', 'wrong number of arguments for \'BF.SWAP\' command'),
+            ('BF.SWAP key temp other', 'wrong number of arguments for \'BF.SWAP\' command'),
+            ('BF.MERGE key', 'wrong number of arguments for \'BF.MERGE\' command'),
+            ('BF.SCANDUMP key', 'wrong number of arguments for \'BF.SCANDUMP\' command'),
+            ('BF.LOADCHUNK key 1', 'wrong number of arguments for \'BF.LOADCHUNK\' command'),
             ('bf.info key capacity size', 'wrong number of arguments for \'BF.INFO\' command'),
             ('BF.INSERT', 'wrong number of arguments for \'BF.INSERT\' command'),
             ('BF.INSERT KEY', 'wrong number of arguments for \'BF.INSERT\' command'),
@@ -76,10 +184,21 @@ class TestBloomCommand(ValkeyBloomTestCaseBase):
             ('BF.MADD KEY', 'wrong number of arguments for \'BF.MADD\' command'),
             ('BF.MEXISTS', 'wrong number of arguments for \'BF.MEXISTS\' command'),
             ('BF.MEXISTS INFO', 'wrong number of arguments for \'BF.MEXISTS\' command'),
+            ('BF.MKEYEXISTS', 'wrong number of arguments for \'BF.MKEYEXISTS\' command'),
+            ('BF.MKEYEXISTS item', 'wrong number of arguments for \'BF.MKEYEXISTS\' command'),
+            ('BF.INTERCARD key', 'wrong number of arguments for \'BF.INTERCARD\' command'),
+            ('BF.INTERCARD key bf bf_non', 'wrong number of arguments for \'BF.INTERCARD\' command'),
             ('BF.RESERVE', 'wrong number of arguments for \'BF.RESERVE\' command'),
             ('BF.RESERVE KEY', 'wrong number of arguments for \'BF.RESERVE\' command'),
             ('BF.RESERVE KEY SSS', 'wrong number of arguments for \'BF.RESERVE\' command'),
             ('BF.RESERVE TT1 0.01 1 NONSCALING test1 test2 test3', 'wrong number of arguments for \'BF.RESERVE\' command'),
+            ('BF.RESERVE TT1 0.01 1 NONSCALING test1 test2 test3 test4', 'wrong number of arguments for \'BF.RESERVE\' command'),
+            ('BF.RESERVE TT1 0.01 1 NONSCALING test1 test2 test3 test4 test5 test6', 'wrong number of arguments for \'BF.RESERVE\' command'),
+            ('BF.RESERVE TT2 0.01 1 LAYOUT', 'wrong number of arguments for \'BF.RESERVE\' command'),
+            ('BF.RESERVE TT2 0.01 1 LAYOUT BLOCKED test1', 'wrong number of arguments for \'BF.RESERVE\' command'),
+            ('BF.RESERVE TT3 0.01 1 HASH', 'wrong number of arguments for \'BF.RESERVE\' command'),
+            ('BF.RESERVE TT3 0.01 1 HASH XXHASH3 test1', 'wrong number of arguments for \'BF.RESERVE\' command'),
+            ('BF.INSERT MISS_VAL HASH', 'wrong number of arguments for \'BF.INSERT\' command'),
             ('BF.RESERVE TT 0.01 1 NONSCALING EXPANSION 1', 'wrong number of arguments for \'BF.RESERVE\' command'),
         ]
 
@@ -104,6 +223,9 @@ class TestBloomCommand(ValkeyBloomTestCaseBase):
             ('BF.MEXISTS hello world5', [0]),
             ('BF.MADD hello world5', [1]),
             ('BF.MEXISTS hello world5 world6 world7', [1, 0, 0]),
+            ('BF.MKEYEXISTS world5 hello', [1]),
+            ('BF.MKEYEXISTS item key hello missing_key', [1, 0, 0]),
+            ('BF.MKEYEXISTS world1 key hello', [0, 1]),
             ('BF.INSERT TEST ITEMS ITEM', [1]),
             ('BF.INSERT TEST CAPACITY 1000 ITEMS ITEM', [0]),
             ('BF.INSERT TEST CAPACITY 200 error 0.50 ITEMS ITEM ITEM1 ITEM2', [0, 1, 1]),
@@ -113,21 +235,63 @@ class TestBloomCommand(ValkeyBloomTestCaseBase):
             ('BF.INSERT TEST_EXPANSION EXPANSION 9 ITEMS ITEM', [1]),
             ('BF.INSERT TEST_CAPACITY CAPACITY 2000 ITEMS ITEM', [1]),
             ('BF.INSERT TEST_ITEMS ITEMS 1 2 3 EXPANSION 2', [1, 1, 1, 1, 0]),
+            ('BF.ADD TEST_ADD CAPACITY 500 ERROR 0.001 EXPANSION 4 HASH XXHASH3 ITEM', 1),
+            ('BF.ADD TEST_ADD NOCREATE CAPACITY 100 ITEM2', 1),
+            ('BF.MADD TEST_MADD NONSCALING CAPACITY 10 ITEMS ITEM1 ITEM2', [1, 1]),
+            ('BF.MADD TEST_MADD NOCREATE ITEMS ITEM3', [1]),
+            ('BF.MADD TEST_MADD_ITEMS ITEMS ITEM1', [1, 1]),
+            # Without ITEMS, arguments named like options are items.
+            ('BF.MADD TEST_MADD_NAMES CAPACITY 100 A B', [1, 1, 1, 1]),
+            ('BF.MADD TEST_MADD_NAMES ATOMIC CONTINUE', [1, 1]),
+            ('BF.MADD TEST_MADD_NAMES CONTINUE A', [0, 0]),
+            ('BF.INFO TEST_ADD CAPACITY', 500),
+            ('BF.INFO TEST_ADD EXPANSION', 4),
+            ('BF.INFO TEST_ADD HASH', b'xxhash3'),
+            ('BF.INFO TEST_MADD CAPACITY', 10),
+            ('BF.INFO TEST_MADD EXPANSION', None),
+            ('BF.EXISTS TEST_MADD_ITEMS ITEMS', 1),
+            ('BF.CARD TEST_MADD_NAMES', 6),
             ('BF.INFO TEST Capacity', 100000),
             ('BF.INFO TEST ITEMS', 5),
             ('BF.INFO TEST filters', 1),
             ('bf.info TEST expansion', 2),
             ('BF.INFO TEST_EXPANSION EXPANSION', 9),
             ('BF.INFO TEST_CAPACITY CAPACITY', 2000),
+            ('BF.EXISTS key item STRICT', 1),
+            ('bf.exists key item404 strict', 0),
+            ('BF.EXISTS TEST404 item', 0),
             ('BF.CARD key', 3),
             ('BF.CARD hello', 5),
             ('BF.CARD TEST', 5),
             ('bf.card HELLO', 0),
+            ('BF.CARD hello ESTIMATE', 5),
+            ('bf.card HELLO estimate', 0),
             ('BF.RESERVE bf 0.01 1000', b'OK'),
             ('BF.RESERVE bf_exp 0.01 1000 EXPANSION 2', b'OK'),
             ('BF.RESERVE bf_non 0.01 1000 NONSCALING', b'OK'),
             ('bf.info bf_exp expansion', 2),
             ('BF.INFO bf_non expansion', None),
+            ('BF.RESERVE bf_blocked 0.01 1000 EXPANSION 4 LAYOUT BLOCKED', b'OK'),
+            ('BF.RESERVE bf_standard 0.01 1000 layout standard NONSCALING', b'OK'),
+            ('BF.INFO bf_blocked LAYOUT', b'blocked'),
+            ('BF.INFO bf_blocked EXPANSION', 4),
+            ('BF.INFO bf_standard layout', b'standard'),
+            ('BF.INFO bf layout', b'standard'),
+            ('BF.RESERVE bf_xxhash 0.01 1000 HASH XXHASH3 LAYOUT BLOCKED', b'OK'),
+            ('BF.RESERVE bf_murmur 0.01 1000 hash murmurhash3', b'OK'),
+            ('BF.INSERT bf_insert_murmur HASH MURMURHASH3 ITEMS item1 item2', [1, 1]),
+            ('BF.MADD bf_xxhash item1 item2', [1, 1]),
+            ('BF.MEXISTS bf_xxhash item1 item2 item3', [1, 1, 0]),
+            ('BF.MEXISTS bf_insert_murmur item1 item2 item3', [1, 1, 0]),
+            ('BF.INFO bf_xxhash HASH', b'xxhash3'),
+            ('BF.INFO bf_murmur hash', b'murmurhash3'),
+            ('BF.INFO bf_insert_murmur HASH', b'murmurhash3'),
+            ('BF.INFO bf HASH', b'siphash'),
+            ('BF.RESERVE bf_large 0.99 5000000000 NONSCALING', b'OK'),
+            ('BF.INSERT bf_insert_large CAPACITY 6000000000 ERROR 0.99 ITEMS item1 item2', [1, 1]),
+            ('BF.INFO bf_large CAPACITY', 5000000000),
+            ('BF.INFO bf_insert_large CAPACITY', 6000000000),
+            ('BF.CARD bf_insert_large', 2),
         ]
 
         for test_case in basic_behavior_test_case:
@@ -146,3 +310,310 @@ class TestBloomCommand(ValkeyBloomTestCaseBase):
         assert bf_info[filter_index] == self.client.execute_command('BF.INFO BF_INFO FILTERS') == 1
         assert bf_info[item_index] == self.client.execute_command('BF.INFO BF_INFO ITEMS') == 0
         assert bf_info[expansion_index] == self.client.execute_command('BF.INFO BF_INFO EXPANSION') == None
+        # The layout, hash function and max number of filters are only replied when requested, so that the reply keeps
+        # the fields of RedisBloom.
+        assert len(bf_info) == 10
+        assert self.client.execute_command('BF.INFO BF_INFO LAYOUT') == b'standard'
+        assert self.client.execute_command('BF.INFO BF_INFO HASH') == b'siphash'
+        # The estimate counts the set bits of every filter, so it is only replied when requested.
+        assert b'Estimated number of items' not in bf_info
+        assert self.client.execute_command('BF.INFO BF_INFO ESTIMATE') == 0
+        assert self.client.execute_command('BF.INFO BF_INFO MAXFILTERS') == 2147483647
+
+    def test_bloom_clear(self):
+        assert self.client.execute_command('BF.RESERVE bf_clear 0.01 100 EXPANSION 4 LAYOUT BLOCKED HASH XXHASH3') == b'OK'
+        bf_info_before = self.client.execute_command('BF.INFO bf_clear')
+        items = ' '.join('item' + str(i) for i in range(500))
+        self.client.execute_command('BF.MADD bf_clear ' + items)
+        assert self.client.execute_command('BF.INFO bf_clear FILTERS') > 1
+        assert self.client.execute_command('BF.CLEAR bf_clear') == b'OK'
+        # The object keeps its properties with only the first filter, which is emptied.
+        assert self.client.execute_command('BF.INFO bf_clear') == bf_info_before
+        assert self.client.execute_command('BF.CARD bf_clear') == 0
+        assert self.client.execute_command('BF.MEXISTS bf_clear item0 item1 item499') == [0, 0, 0]
+        assert self.client.execute_command('BF.ADD bf_clear item0') == 1
+        assert self.client.execute_command('BF.EXISTS bf_clear item0') == 1
+        # Clearing a non scaling filter which is full allows adding items again.
+        assert self.client.execute_command('BF.RESERVE bf_clear_non 0.01 2 NONSCALING') == b'OK'
+        assert self.client.execute_command('BF.MADD bf_clear_non item1 item2') == [1, 1]
+        self.verify_error_response(self.client, 'BF.ADD bf_clear_non item3', 'non scaling filter is full')
+        assert self.client.execute_command('BF.CLEAR bf_clear_non') == b'OK'
+        assert self.client.execute_command('BF.ADD bf_clear_non item3') == 1
+        assert self.client.execute_command('SET string_key value') == b'OK'
+        self.verify_error_response(self.client, 'BF.CLEAR string_key', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+
+    def test_bloom_compact_and_swap(self):
+        assert self.client.execute_command('BF.RESERVE bf_scaled 0.01 100 EXPANSION 1 HASH MURMURHASH3') == b'OK'
+        items = ['item' + str(i) for i in range(1000)]
+        self.client.execute_command('BF.MADD bf_scaled ' + ' '.join(items))
+        num_filters = self.client.execute_command('BF.INFO bf_scaled FILTERS')
+        cardinality = self.client.execute_command('BF.CARD bf_scaled')
+        assert num_filters > 5
+        # The temporary object has the properties of the scaled object and a single filter sized for its items.
+        assert self.client.execute_command('BF.COMPACT bf_scaled bf_scaled_tmp') == b'OK'
+        assert self.client.execute_command('BF.INFO bf_scaled_tmp FILTERS') == 1
+        assert self.client.execute_command('BF.INFO bf_scaled_tmp CAPACITY') == cardinality
+        assert self.client.execute_command('BF.INFO bf_scaled_tmp EXPANSION') == 1
+        assert self.client.execute_command('BF.INFO bf_scaled_tmp HASH') == b'murmurhash3'
+        assert self.client.execute_command('BF.CARD bf_scaled_tmp') == 0
+        assert self.client.execute_command('DEL bf_scaled_tmp') == 1
+        # Rebuild the object from the item stream and replace the scaled object with it. An explicit capacity can be
+        # provided to leave room for the items.
+        assert self.client.execute_command('BF.COMPACT bf_scaled bf_scaled_tmp CAPACITY 1000') == b'OK'
+        self.client.execute_command('BF.MADD bf_scaled_tmp ' + ' '.join(items))
+        assert self.client.execute_command('BF.INFO bf_scaled_tmp FILTERS') == 1
+        assert self.client.execute_command('BF.SWAP bf_scaled bf_scaled_tmp') == b'OK'
+        assert self.client.execute_command('EXISTS bf_scaled_tmp') == 0
+        assert self.client.execute_command('BF.INFO bf_scaled FILTERS') == 1
+        assert self.client.execute_command('BF.INFO bf_scaled HASH') == b'murmurhash3'
+        assert self.client.execute_command('BF.MEXISTS bf_scaled item0 item500 item999') == [1, 1, 1]
+        assert self.client.execute_command('BF.COMPACT bf_scaled bf_scaled_tmp CAPACITY 5000') == b'OK'
+        assert self.client.execute_command('BF.INFO bf_scaled_tmp CAPACITY') == 5000
+        # Swapping a key with itself is a no-op.
+        assert self.client.execute_command('BF.SWAP bf_scaled_tmp bf_scaled_tmp') == b'OK'
+        assert self.client.execute_command('BF.INFO bf_scaled_tmp CAPACITY') == 5000
+        assert self.client.execute_command('SET string_key value') == b'OK'
+        self.verify_error_response(self.client, 'BF.SWAP string_key bf_scaled_tmp', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+        self.verify_error_response(self.client, 'BF.SWAP bf_scaled string_key', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+        self.verify_error_response(self.client, 'BF.COMPACT string_key bf_new_tmp', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+        self.verify_error_response(self.client, 'BF.COMPACT bf_scaled string_key', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+
+    def test_bloom_scandump_and_loadchunk(self):
+        # Objects of every layout and hash function are restored from their header and chunks.
+        create_cmds = [
+            'BF.RESERVE {dump}:standard 0.01 1000',
+            'BF.RESERVE {dump}:blocked 0.001 1000 EXPANSION 4 LAYOUT BLOCKED HASH XXHASH3',
+            'BF.RESERVE {dump}:redisbloom 0.01 1000 LAYOUT REDISBLOOM HASH MURMURHASH64A',
+            'BF.RESERVE {dump}:nonscaling 0.01 1000 NONSCALING HASH MURMURHASH3',
+        ]
+        items = ['item' + str(i) for i in range(2000)]
+        for create_cmd in create_cmds:
+            key = create_cmd.split()[1]
+            assert self.client.execute_command(create_cmd) == b'OK'
+            self.client.execute_command('BF.INSERT ' + key + ' CONTINUE ITEMS ' + ' '.join(items))
+            chunks = []
+            iterator = 0
+            while True:
+                iterator, data = self.client.execute_command('BF.SCANDUMP', key, iterator)
+                if iterator == 0:
+                    assert data == b''
+                    break
+                chunks.append((iterator, data))
+            # The header comes first, with the iterator 1, followed by a chunk per filter.
+            assert chunks[0][0] == 1
+            assert len(chunks) == 1 + self.client.execute_command('BF.INFO ' + key + ' FILTERS')
+            for iterator, data in chunks:
+                assert self.client.execute_command('BF.LOADCHUNK', key + ':restored', iterator, data) == b'OK'
+            assert self.client.execute_command('BF.INFO ' + key + ':restored') == self.client.execute_command('BF.INFO ' + key)
+            assert self.client.execute_command('BF.MEXISTS ' + key + ':restored item0 item999') == self.client.execute_command('BF.MEXISTS ' + key + ' item0 item999')
+            assert self.client.execute_command('BF.CARD ' + key + ':restored') == self.client.execute_command('BF.CARD ' + key)
+        # The header and chunks of RedisBloom are byte compatible, and load objects with its layout and hash functions.
+        for force64, noround, hash_function in [(True, True, b'murmurhash64a'), (False, False, b'murmurhash2')]:
+            key = 'rb_' + hash_function.decode()
+            header, chunk = self.build_redisbloom_scandump([b'item1', b'item2', b'item3'], force64=force64, noround=noround)
+            assert self.client.execute_command('BF.LOADCHUNK', key, 1, header) == b'OK'
+            assert self.client.execute_command('BF.LOADCHUNK', key, 1 + len(chunk), chunk) == b'OK'
+            assert self.client.execute_command('BF.MEXISTS ' + key + ' item1 item2 item3 item4') == [1, 1, 1, 0]
+            assert self.client.execute_command('BF.INFO ' + key + ' LAYOUT') == b'redisbloom'
+            assert self.client.execute_command('BF.INFO ' + key + ' HASH') == hash_function
+            assert self.client.execute_command('BF.CARD ' + key) == 3
+            # Objects with the layout and hash function of RedisBloom are dumped as RedisBloom dumps them.
+            if noround:
+                iterator, dumped_header = self.client.execute_command('BF.SCANDUMP', key, 0)
+                assert iterator == 1
+                assert struct.unpack('<QIIIQQQddIQB', dumped_header) == pytest.approx(struct.unpack('<QIIIQQQddIQB', header))
+            assert self.client.execute_command('BF.SCANDUMP', key, 1) == [1 + len(chunk), chunk]
+            assert self.client.execute_command('BF.SCANDUMP', key, 1 + len(chunk)) == [0, b'']
+        header, chunk = self.build_redisbloom_scandump([b'item1'])
+        self.verify_error_response(self.client, 'BF.LOADCHUNK rb_murmurhash64a 1 ' + 'x' * len(header), 'item exists')
+        assert self.client.execute_command('BF.LOADCHUNK', 'rb_partial', 1, header) == b'OK'
+        with pytest.raises(ResponseError, match='invalid chunk - Too big for current filter'):
+            self.client.execute_command('BF.LOADCHUNK', 'rb_partial', 1 + len(chunk) + 1, chunk + b'x')
+        with pytest.raises(ResponseError, match='received bad data'):
+            self.client.execute_command('BF.LOADCHUNK', 'rb_bad', 1, header[:-1])
+        # Headers of filters which exceed the memory limit of bloom objects are rejected without allocating them.
+        huge_header = struct.pack('<QIII', 0, 1, 5, 2) + struct.pack('<QQQddIQB', 1 << 50, 1 << 53, 0, 0.01, 9.585, 7, 1000, 0)
+        with pytest.raises(ResponseError, match='operation exceeds bloom object memory limit'):
+            self.client.execute_command('BF.LOADCHUNK', 'rb_huge', 1, huge_header)
+        assert self.client.execute_command('EXISTS rb_huge') == 0
+
+    def test_bloom_mkeyexists(self):
+        assert self.client.execute_command('BF.MADD {day}:1 item1 item2') == [1, 1]
+        assert self.client.execute_command('BF.MADD {day}:2 item2 item3') == [1, 1]
+        assert self.client.execute_command('SET {day}:string value') == b'OK'
+        assert self.client.execute_command('BF.MKEYEXISTS item2 {day}:1 {day}:2 {day}:3') == [1, 1, 0]
+        assert self.client.execute_command('BF.MKEYEXISTS item3 {day}:1 {day}:2') == [0, 1]
+        self.verify_error_response(self.client, 'BF.MKEYEXISTS item1 {day}:1 {day}:string', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+        # Every argument after the item is declared as a key.
+        assert self.client.execute_command('COMMAND GETKEYS BF.MKEYEXISTS item1 {day}:1 {day}:2 {day}:3') == [b'{day}:1', b'{day}:2', b'{day}:3']
+
+    def test_bloom_wrong_type(self):
+        assert self.client.execute_command('SET string_key value') == b'OK'
+        wrong_type_cmds = [
+            'BF.ADD string_key item', 'BF.MADD string_key item1 item2', 'BF.EXISTS string_key item',
+            'BF.EXISTS string_key item STRICT', 'BF.MEXISTS string_key item1 item2', 'BF.CARD string_key',
+            'BF.INFO string_key', 'BF.INFO string_key CAPACITY', 'BF.RESERVE string_key 0.01 1000',
+            'BF.INSERT string_key ITEMS item', 'BF.INSERT string_key NOCREATE ITEMS item',
+            'BF.SCANDUMP string_key 0', 'BF.LOADCHUNK string_key 1 data',
+        ]
+        for cmd in wrong_type_cmds:
+            self.verify_error_response(self.client, cmd, 'WRONGTYPE Operation against a key holding the wrong kind of value')
+        assert self.client.execute_command('GET string_key') == b'value'
+
+    def test_bloom_add_modes(self):
+        assert self.client.execute_command('BF.RESERVE bf_non 0.01 3 NONSCALING') == b'OK'
+        assert self.client.execute_command('BF.ADD bf_non item1') == 1
+        # By default, adding stops at the first item which cannot be added and the items before it remain added.
+        result = self.client.execute_command('BF.MADD bf_non item1 item2 item3 item4 item5')
+        assert result[:3] == [0, 1, 1] and len(result) == 4
+        assert isinstance(result[3], ResponseError) and str(result[3]) == 'non scaling filter is full'
+        assert self.client.execute_command('BF.CARD bf_non') == 3
+        # Atomic adds either add every item or none of them.
+        assert self.client.execute_command('BF.RESERVE bf_atomic 0.01 3 NONSCALING') == b'OK'
+        assert self.client.execute_command('BF.ADD bf_atomic item1') == 1
+        self.verify_error_response(self.client, 'BF.MADD bf_atomic ATOMIC ITEMS item1 item2 item3 item4', 'non scaling filter is full')
+        self.verify_error_response(self.client, 'BF.INSERT bf_atomic ATOMIC ITEMS item2 item3 item4', 'non scaling filter is full')
+        assert self.client.execute_command('BF.CARD bf_atomic') == 1
+        assert self.client.execute_command('BF.MEXISTS bf_atomic item2 item3 item4') == [0, 0, 0]
+        # Items which exist already or repeat within the batch do not need any capacity.
+        assert self.client.execute_command('BF.INSERT bf_atomic ATOMIC ITEMS item1 item2 item2 item3') == [0, 1, 0, 1]
+        assert self.client.execute_command('BF.CARD bf_atomic') == 3
+        # A bloom object is not created by an atomic add which fails.
+        self.verify_error_response(self.client, 'BF.INSERT bf_atomic_new CAPACITY 2 NONSCALING ATOMIC ITEMS item1 item2 item3', 'non scaling filter is full')
+        assert self.client.execute_command('EXISTS bf_atomic_new') == 0
+        assert self.client.execute_command('BF.MADD bf_atomic_scaling CAPACITY 2 EXPANSION 2 ATOMIC ITEMS item1 item2 item3 item4 item5') == [1, 1, 1, 1, 1]
+        # Continuing after errors replies with an error for every item which could not be added.
+        assert self.client.execute_command('BF.RESERVE bf_continue 0.01 2 NONSCALING') == b'OK'
+        result = self.client.execute_command('BF.INSERT bf_continue CONTINUE ITEMS item1 item2 item3 item1 item4')
+        assert result[:2] == [1, 1] and result[3] == 0
+        assert all(isinstance(result[i], ResponseError) and str(result[i]) == 'non scaling filter is full' for i in [2, 4])
+        result = self.client.execute_command('BF.MADD bf_continue CONTINUE ITEMS item5 item2')
+        assert isinstance(result[0], ResponseError) and result[1] == 0
+
+    def test_bloom_intercard(self):
+        items_one = ' '.join('item' + str(i) for i in range(0, 2000))
+        items_two = ' '.join('item' + str(i) for i in range(1000, 3000))
+        assert self.client.execute_command('BF.RESERVE {aud}:1 0.001 5000') == b'OK'
+        assert self.client.execute_command('BF.RESERVE {aud}:2 0.001 5000') == b'OK'
+        self.client.execute_command('BF.MADD {aud}:1 ' + items_one)
+        self.client.execute_command('BF.MADD {aud}:2 ' + items_two)
+        # 1000 items were added to both objects.
+        intersection = self.client.execute_command('BF.INTERCARD {aud}:1 {aud}:2')
+        assert abs(intersection - 1000) < 100
+        assert self.client.execute_command('BF.INTERCARD {aud}:2 {aud}:1') == intersection
+        assert self.client.execute_command('BF.INTERCARD {aud}:1 {aud}:missing') == 0
+        assert self.client.execute_command('BF.INTERCARD {aud}:missing {aud}:1') == 0
+        # Objects which were created with different parameters cannot be intersected.
+        assert self.client.execute_command('BF.RESERVE {aud}:capacity 0.001 10000') == b'OK'
+        assert self.client.execute_command('BF.RESERVE {aud}:fp_rate 0.01 5000') == b'OK'
+        assert self.client.execute_command('BF.RESERVE {aud}:layout 0.001 5000 LAYOUT BLOCKED') == b'OK'
+        assert self.client.execute_command('BF.RESERVE {aud}:hash 0.001 5000 HASH XXHASH3') == b'OK'
+        for incompatible_key in ['{aud}:capacity', '{aud}:fp_rate', '{aud}:layout', '{aud}:hash']:
+            self.verify_error_response(self.client, 'BF.INTERCARD {aud}:1 ' + incompatible_key, 'bloom objects have incompatible filters (layout, hash function or filter sizes differ)')
+        assert self.client.execute_command('SET {aud}:string value') == b'OK'
+        self.verify_error_response(self.client, 'BF.INTERCARD {aud}:1 {aud}:string', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+
+    def test_bloom_merge(self):
+        items_one = ' '.join('item' + str(i) for i in range(0, 2000))
+        items_two = ' '.join('item' + str(i) for i in range(1000, 3000))
+        assert self.client.execute_command('BF.RESERVE {aud}:1 0.001 1000 HASH XXHASH3') == b'OK'
+        assert self.client.execute_command('BF.RESERVE {aud}:2 0.001 1000 HASH XXHASH3') == b'OK'
+        self.client.execute_command('BF.MADD {aud}:1 ' + items_one)
+        self.client.execute_command('BF.MADD {aud}:2 ' + items_two)
+        # The destination is created as a copy of the first source, and holds the items of all the sources.
+        assert self.client.execute_command('BF.MERGE {aud}:all {aud}:1 {aud}:2') == b'OK'
+        assert self.client.execute_command('BF.INFO {aud}:all HASH') == b'xxhash3'
+        assert self.client.execute_command('BF.INFO {aud}:all FILTERS') == self.client.execute_command('BF.INFO {aud}:2 FILTERS')
+        assert self.client.execute_command('BF.MEXISTS {aud}:all item0 item1500 item2999') == [1, 1, 1]
+        # Merging into an existing destination keeps its items, and merging it into itself is a no-op.
+        assert self.client.execute_command('BF.MERGE {aud}:1 {aud}:2 {aud}:1') == b'OK'
+        assert self.client.execute_command('BF.MEXISTS {aud}:1 item0 item2999') == [1, 1]
+        # Objects which were created with different parameters cannot be merged, and the destination is left unchanged.
+        assert self.client.execute_command('BF.RESERVE {aud}:capacity 0.001 10000') == b'OK'
+        assert self.client.execute_command('BF.RESERVE {aud}:layout 0.001 1000 LAYOUT BLOCKED HASH XXHASH3') == b'OK'
+        dump = self.client.execute_command('DUMP {aud}:all')
+        for incompatible_key in ['{aud}:capacity', '{aud}:layout']:
+            self.verify_error_response(self.client, 'BF.MERGE {aud}:all {aud}:2 ' + incompatible_key, 'bloom objects have incompatible filters (layout, hash function or filter sizes differ)')
+        assert self.client.execute_command('DUMP {aud}:all') == dump
+        assert self.client.execute_command('SET {aud}:string value') == b'OK'
+        self.verify_error_response(self.client, 'BF.MERGE {aud}:string {aud}:1', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+        self.verify_error_response(self.client, 'BF.MERGE {aud}:all {aud}:string', 'WRONGTYPE Operation against a key holding the wrong kind of value')
+        self.verify_error_response(self.client, 'BF.MERGE {aud}:new {aud}:1 {aud}:missing', 'not found')
+        assert self.client.execute_command('EXISTS {aud}:new') == 0
+
+    def test_bloom_hash_function_config(self):
+        assert self.client.execute_command('CONFIG GET bloom-hash-function') == [b'bloom-hash-function', b'siphash']
+        assert self.client.execute_command('BF.ADD before_config item') == 1
+        assert self.client.execute_command('CONFIG SET bloom-hash-function xxhash3') == b'OK'
+        # Objects created afterwards use the new default, existing objects keep their hash function.
+        assert self.client.execute_command('BF.ADD after_config item') == 1
+        assert self.client.execute_command('BF.RESERVE reserved_after_config 0.01 1000') == b'OK'
+        assert self.client.execute_command('BF.INFO after_config HASH') == b'xxhash3'
+        assert self.client.execute_command('BF.INFO reserved_after_config HASH') == b'xxhash3'
+        assert self.client.execute_command('BF.INFO before_config HASH') == b'siphash'
+        assert self.client.execute_command('BF.EXISTS before_config item') == 1
+        assert self.client.execute_command('BF.EXISTS after_config item') == 1
+        # An explicit HASH argument takes precedence over the default.
+        assert self.client.execute_command('BF.RESERVE explicit_hash 0.01 1000 HASH siphash') == b'OK'
+        assert self.client.execute_command('BF.INFO explicit_hash HASH') == b'siphash'
+        try:
+            self.client.execute_command('CONFIG SET bloom-hash-function sha256')
+            assert False
+        except ResponseError as e:
+            assert 'must be one of the following' in str(e)
+        assert self.client.execute_command('CONFIG SET bloom-hash-function siphash') == b'OK'
+
+    def test_bloom_max_filters_per_object_config(self):
+        assert self.client.execute_command('CONFIG GET bloom-max-filters-per-object') == [b'bloom-max-filters-per-object', b'2147483647']
+        assert self.client.execute_command('BF.INSERT scaled CAPACITY 1 ITEMS item1 item2') == [1, 1]
+        assert self.client.execute_command('BF.INFO scaled FILTERS') == 2
+        assert self.client.execute_command('CONFIG SET bloom-max-filters-per-object 2') == b'OK'
+        assert self.client.execute_command('BF.INFO scaled MAXFILTERS') == 2
+        # The filter created by the last scale out has room for one more item.
+        assert self.client.execute_command('BF.ADD scaled item4') == 1
+        self.verify_error_response(self.client, 'BF.ADD scaled item5', 'max number of scaling filters reached')
+        self.verify_error_response(self.client, 'BF.MADD scaled ATOMIC ITEMS item5 item6', 'max number of scaling filters reached')
+        # Lowering the limit below the number of filters of an object stops it from scaling out further.
+        assert self.client.execute_command('CONFIG SET bloom-max-filters-per-object 1') == b'OK'
+        self.verify_error_response(self.client, 'BF.ADD scaled item5', 'max number of scaling filters reached')
+        assert self.client.execute_command('BF.EXISTS scaled item4') == 1
+        assert self.client.execute_command('BF.INFO scaled FILTERS') == 2
+        # Objects with more filters than the limit are still loaded, from DUMP payloads as from BF.LOADCHUNK.
+        dump = self.client.execute_command('DUMP scaled')
+        assert self.client.execute_command('RESTORE scaled_restored 0', dump) == b'OK'
+        assert self.client.execute_command('BF.INFO scaled_restored FILTERS') == 2
+        iterator = 0
+        while True:
+            iterator, data = self.client.execute_command('BF.SCANDUMP scaled', iterator)
+            if iterator == 0:
+                break
+            assert self.client.execute_command('BF.LOADCHUNK scaled_loaded', iterator, data) == b'OK'
+        assert self.client.execute_command('BF.INFO scaled_loaded FILTERS') == 2
+        assert self.client.execute_command('BF.EXISTS scaled_loaded item4') == 1
+        for invalid_value in ['0', '-1', '2147483648']:
+            try:
+                self.client.execute_command('CONFIG SET bloom-max-filters-per-object ' + invalid_value)
+                assert False
+            except ResponseError as e:
+                assert 'argument must be between' in str(e)
+        assert self.client.execute_command('CONFIG SET bloom-max-filters-per-object 2147483647') == b'OK'
+
+    def test_bloom_memory_usage_limit_config(self):
+        assert self.client.execute_command('CONFIG GET bloom-memory-usage-limit') == [b'bloom-memory-usage-limit', b'134217728']
+        assert self.client.execute_command('BF.INSERT scaled CAPACITY 1000 EXPANSION 10 ITEMS item1') == [1]
+        assert self.client.execute_command('CONFIG SET bloom-memory-usage-limit 10000') == b'OK'
+        self.verify_error_response(self.client, 'BF.RESERVE large 0.01 10000', 'operation exceeds bloom object memory limit')
+        # Objects stop scaling out once the filter created by a scale out would exceed the limit.
+        items = ' '.join('item' + str(i) for i in range(2, 1002))
+        self.verify_error_response(self.client, 'BF.MADD scaled ATOMIC ITEMS ' + items, 'operation exceeds bloom object memory limit')
+        result = self.client.execute_command('BF.MADD scaled ' + items)
+        assert len(result) == 1000 and result[:-1] == [1] * 999
+        assert isinstance(result[-1], ResponseError) and str(result[-1]) == 'operation exceeds bloom object memory limit'
+        assert self.client.execute_command('BF.INFO scaled FILTERS') == 1
+        # Objects of more than 4 billion items are created once the limit is raised above the size of their filter.
+        assert self.client.execute_command('CONFIG SET bloom-memory-usage-limit 10000000') == b'OK'
+        self.verify_error_response(self.client, 'BF.RESERVE huge 0.99 5000000000', 'operation exceeds bloom object memory limit')
+        assert self.client.execute_command('CONFIG SET bloom-memory-usage-limit 20000000') == b'OK'
+        assert self.client.execute_command('BF.RESERVE huge 0.99 5000000000') == b'OK'
+        assert self.client.execute_command('BF.INFO huge CAPACITY') == 5000000000
+        assert self.client.execute_command('CONFIG SET bloom-memory-usage-limit 134217728') == b'OK'
@@ -0,0 +1,84 @@
+import pytest
+from valkey import ResponseError
+from valkey_bloom_test_case import ValkeyBloomTestCaseBase
+from valkeytests.valkey_test_case import ValkeyServerHandle
+from valkeytests.util.waiters import wait_for_equal
+from valkeytests.conftest import resource_port_tracker
+
+CROSSSLOT_ERROR = "CROSSSLOT Keys in request don't hash to the same slot"
+
+class ValkeyClusterNode(ValkeyServerHandle):
+    """ Server started in cluster mode, with a nodes.conf file of its own in the shared test directory.
+    """
+    def __init__(self, bind_ip, port, port_tracker, server_path, cwd='.', server_id=0):
+        super(ValkeyClusterNode, self).__init__(bind_ip, port, port_tracker, server_path, cwd, server_id)
+        self.args["cluster-enabled"] = "yes"
+        self.args["cluster-config-file"] = "nodes_{}.conf".format(port)
+
+class TestBloomCluster(ValkeyBloomTestCaseBase):
+
+    def get_valkey_handle(self):
+        return ValkeyClusterNode
+
+    def setup_cluster(self):
+        """ Starts a second node and forms a cluster in which the second node only owns the slot of the `{b}` hash tag,
+        and the first node owns every other slot.
+        """
+        self.nodes = [self.server, self.create_server(testdir=self.testdir, server_path=self.server_path)]
+        self.nodes[1].set_startup_args(self._get_valkey_args())
+        self.nodes[1].start()
+        slot_b = self.client.execute_command('CLUSTER KEYSLOT {b}')
+        assert self.client.execute_command(f'CLUSTER ADDSLOTSRANGE 0 {slot_b - 1} {slot_b + 1} 16383') == b'OK'
+        assert self.nodes[1].client.execute_command(f'CLUSTER ADDSLOTS {slot_b}') == b'OK'
+        assert self.client.execute_command(f'CLUSTER MEET 127.0.0.1 {self.nodes[1].port}') == b'OK'
+        for node in self.nodes:
+            wait_for_equal(lambda: node.client.execute_command('CLUSTER INFO')['cluster_state'], 'ok')
+
+    def teardown(self):
+        for node in getattr(self, 'nodes', [])[1:]:
+            node.exit()
+        super(TestBloomCluster, self).teardown()
+
+    def test_cluster_multi_key_commands_in_same_slot(self):
+        self.setup_cluster()
+        assert self.client.execute_command('BF.MADD {a}:1 item1 item2') == [1, 1]
+        assert self.client.execute_command('BF.MADD {a}:2 item2 item3') == [1, 1]
+        assert self.client.execute_command('BF.MKEYEXISTS item2 {a}:1 {a}:2 {a}:missing') == [1, 1, 0]
+        assert self.client.execute_command('BF.INTERCARD {a}:1 {a}:2') == 1
+        assert self.client.execute_command('BF.MERGE {a}:all {a}:1 {a}:2') == b'OK'
+        assert self.client.execute_command('BF.MEXISTS {a}:all item1 item2 item3') == [1, 1, 1]
+        assert self.client.execute_command('BF.COMPACT {a}:all {a}:tmp') == b'OK'
+        assert self.client.execute_command('BF.MADD {a}:tmp item1 item2 item3') == [1, 1, 1]
+        assert self.client.execute_command('BF.SWAP {a}:all {a}:tmp') == b'OK'
+        assert self.client.execute_command('BF.CARD {a}:all') == 3
+        # The commands run on the node owning the slot of the keys.
+        assert self.nodes[1].client.execute_command('BF.MADD {b}:1 item1 item2') == [1, 1]
+        assert self.nodes[1].client.execute_command('BF.MERGE {b}:all {b}:1') == b'OK'
+        assert self.nodes[1].client.execute_command('BF.MKEYEXISTS item1 {b}:1 {b}:all') == [1, 1]
+        with pytest.raises(ResponseError, match='MOVED'):
+            self.client.execute_command('BF.MKEYEXISTS item1 {b}:1 {b}:all')
+
+    def test_cluster_multi_key_commands_across_slots(self):
+        self.setup_cluster()
+        assert self.client.execute_command('BF.ADD {a}:1 item') == 1
+        assert self.client.execute_command('BF.ADD {c}:1 item') == 1
+        assert self.nodes[1].client.execute_command('BF.ADD {b}:1 item') == 1
+        # Keys of different slots are rejected, whether the slots are owned by the same node or by different nodes.
+        cross_slot_cmds = [
+            'BF.MKEYEXISTS item {a}:1 {c}:1',
+            'BF.MKEYEXISTS item {a}:1 {a}:missing {b}:1',
+            'BF.INTERCARD {a}:1 {c}:1',
+            'BF.MERGE {a}:1 {c}:1',
+            'BF.MERGE {a}:all {a}:1 {b}:1',
+            'BF.COMPACT {a}:1 {c}:tmp',
+            'BF.SWAP {a}:1 {c}:1',
+        ]
+        for cmd in cross_slot_cmds:
+            self.verify_error_response(self.client, cmd, CROSSSLOT_ERROR)
+        assert self.client.execute_command('EXISTS {a}:all') == 0
+        assert self.client.execute_command('DBSIZE') == 2
+        # Scripts which allow cross slot keys can call commands with keys of different slots, which the module rejects.
+        script = "#!lua flags=allow-cross-slot-keys\nreturn redis.call('BF.MERGE', '{a}:1', '{c}:1')"
+        with pytest.raises(ResponseError, match='CROSSSLOT'):
+            self.client.execute_command('EVAL', script, 0)
+        assert self.client.execute_command('BF.CARD {a}:1') == 1
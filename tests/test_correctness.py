@@ -23,6 +23,8 @@ class TestBloomCorrectness(ValkeyBloomTestCaseBase):
         info_dict = dict(zip(it, it))
         assert info_dict[b'Capacity'] == capacity
         assert info_dict[b'Number of items inserted'] == capacity
+        # The estimate from the set bits should be close to the number of unique items inserted.
+        assert abs(client.execute_command(f'BF.INFO {filter_name} ESTIMATE') - capacity) < capacity * 0.05
         assert info_dict[b'Number of filters'] == 1
         assert info_dict[b'Size'] > 0
         assert info_dict[b'Expansion rate'] == None
@@ -129,3 +131,38 @@ class TestBloomCorrectness(ValkeyBloomTestCaseBase):
         info_dict = dict(zip(it, it))
         # Validate correctness on a copy of a scaling bloom filter.
         self.validate_copied_bloom_correctness(client, filter_name, item_prefix, add_operation_idx, expected_fp_rate, fp_margin, info_dict)
+
+    def test_blocked_layout_filter(self):
+        client = self.server.get_new_client()
+        item_prefix = self.generate_random_string()
+        expected_fp_rate = 0.001
+        initial_capacity = 10000
+        expansion = 2
+        num_filters_to_scale = 3
+        filter_name = "filter1"
+        # Create a scaling bloom filter with the blocked layout and validate its behavior.
+        assert client.execute_command(f'BF.RESERVE {filter_name} {expected_fp_rate} {initial_capacity} EXPANSION {expansion} LAYOUT BLOCKED') == b"OK"
+        total_error_count = 0
+        add_operation_idx = 0
+        for filter_idx in range(1, num_filters_to_scale + 1):
+            expected_total_capacity = initial_capacity * ((expansion ** filter_idx) - 1)
+            error_count, new_add_operation_idx = self.add_items_till_capacity(client, filter_name, expected_total_capacity, add_operation_idx + 1, item_prefix)
+            add_operation_idx = new_add_operation_idx
+            total_error_count += error_count
+            info = client.execute_command(f'BF.INFO {filter_name}')
+            it = iter(info)
+            info_dict = dict(zip(it, it))
+            assert info_dict[b'Capacity'] == expected_total_capacity
+            assert info_dict[b'Number of filters'] == filter_idx
+            assert client.execute_command(f'BF.INFO {filter_name} LAYOUT') == b'blocked'
+        fp_margin = 0.002
+        self.fp_assert(total_error_count, add_operation_idx, expected_fp_rate, fp_margin)
+        # False negatives should not be possible.
+        error_count, num_operations = self.check_items_exist(client, filter_name, 1, add_operation_idx, True, item_prefix)
+        assert error_count == 0
+        # False positives should be close to the configured fp_rate.
+        error_count, num_operations = self.check_items_exist(client, filter_name, add_operation_idx + 1, add_operation_idx * 2, False, item_prefix)
+        self.fp_assert(error_count, num_operations, expected_fp_rate, fp_margin)
+        # Validate correctness on a copy of a blocked bloom filter.
+        self.validate_copied_bloom_correctness(client, filter_name, item_prefix, add_operation_idx, expected_fp_rate, fp_margin, info_dict)
+        assert client.execute_command('BF.INFO filter_copy LAYOUT') == b'blocked'
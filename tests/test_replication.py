@@ -109,9 +109,12 @@ class TestBloomReplication(ReplicationTestCase):
         self.replicas[0].client.execute_command('CONFIG RESETSTAT')
 
         # Write commands with errors are not replicated.
+        # Commands with the wrong number of arguments are rejected by the server based on their arity, so use errors
+        # returned by the module instead.
+        self.client.execute_command('SET string_key value')
         invalid_bloom_write_cmds = [
-            ('BF.ADD', 'BF.ADD key item1 item2'),
-            ('BF.MADD', 'BF.MADD key'),
+            ('BF.ADD', 'BF.ADD string_key item1'),
+            ('BF.MADD', 'BF.MADD string_key item1 item2'),
             ('BF.RESERVE', 'BF.RESERVE key 1.001 100000'),
             ('BF.INSERT', 'BF.INSERT key CAPACITY 0 items item'),
         ]
@@ -127,3 +130,40 @@ class TestBloomReplication(ReplicationTestCase):
             assert primary_cmd_stats["calls"] == 1
             assert primary_cmd_stats["failed_calls"] == 1
             assert ('cmdstat_' + prefix) not in self.replicas[0].client.info("Commandstats")
+
+    def test_clear_replication(self):
+        self.setup_replication(num_replicas=1)
+        assert self.client.execute_command('BF.RESERVE key 0.001 1000 EXPANSION 4') == b'OK'
+        assert self.client.execute_command('BF.MADD key item1 item2') == [1, 1]
+        assert self.client.execute_command('BF.CLEAR key') == b'OK'
+        self.waitForReplicaToSyncUp(self.replicas[0])
+        assert self.replicas[0].client.execute_command('BF.MEXISTS key item1 item2') == [0, 0]
+        assert self.replicas[0].client.execute_command('BF.INFO key') == self.client.execute_command('BF.INFO key')
+        assert self.replicas[0].client.info("Commandstats")['cmdstat_BF.CLEAR']["calls"] == 1
+
+    def test_compact_and_swap_replication(self):
+        self.setup_replication(num_replicas=1)
+        assert self.client.execute_command('BF.RESERVE key 0.01 10 EXPANSION 1') == b'OK'
+        items = ' '.join('item' + str(i) for i in range(100))
+        self.client.execute_command('BF.MADD key ' + items)
+        assert self.client.execute_command('BF.COMPACT key temp CAPACITY 100') == b'OK'
+        self.client.execute_command('BF.MADD temp ' + items)
+        assert self.client.execute_command('BF.SWAP key temp') == b'OK'
+        self.waitForReplicaToSyncUp(self.replicas[0])
+        assert self.replicas[0].client.execute_command('EXISTS temp') == 0
+        assert self.replicas[0].client.execute_command('BF.INFO key FILTERS') == 1
+        assert self.replicas[0].client.execute_command('BF.INFO key') == self.client.execute_command('BF.INFO key')
+        assert self.replicas[0].client.execute_command('BF.MEXISTS key item0 item99') == [1, 1]
+
+    def test_scandump_and_loadchunk_replication(self):
+        self.setup_replication(num_replicas=1)
+        assert self.client.execute_command('BF.RESERVE key 0.01 1000 LAYOUT BLOCKED') == b'OK'
+        assert self.client.execute_command('BF.MADD key item1 item2') == [1, 1]
+        iterator, header = self.client.execute_command('BF.SCANDUMP key 0')
+        assert self.client.execute_command('BF.LOADCHUNK', 'restored', iterator, header) == b'OK'
+        iterator, chunk = self.client.execute_command('BF.SCANDUMP', 'key', iterator)
+        assert self.client.execute_command('BF.LOADCHUNK', 'restored', iterator, chunk) == b'OK'
+        self.waitForReplicaToSyncUp(self.replicas[0])
+        assert self.replicas[0].client.execute_command('BF.MEXISTS restored item1 item2 item3') == [1, 1, 0]
+        assert self.replicas[0].client.execute_command('BF.INFO restored') == self.client.execute_command('BF.INFO key')
+        assert self.replicas[0].client.info("Commandstats")['cmdstat_BF.LOADCHUNK']["calls"] == 2
@@ -34,3 +34,207 @@ class TestBloomSaveRestore(ValkeyBloomTestCaseBase):
         assert bf_exists_result_2 == 1
         bf_info_result_2 = client.execute_command('BF.INFO testSave')
         assert bf_info_result_2 == bf_info_result_1
+
+    def test_blocked_layout_save_and_restore(self):
+        client = self.server.get_new_client()
+        assert client.execute_command('BF.RESERVE testBlocked 0.001 1000 LAYOUT BLOCKED') == b'OK'
+        assert client.execute_command('BF.MADD testBlocked item1 item2 item3') == [1, 1, 1]
+        bf_info_result_1 = client.execute_command('BF.INFO testBlocked')
+        dump = client.execute_command('DUMP testBlocked')
+
+        # save rdb, restart sever
+        client.bgsave()
+        self.server.wait_for_save_done()
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        assert self.server.is_rdb_done_loading()
+
+        # verify restore results
+        assert self.client.execute_command('BF.MEXISTS testBlocked item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert self.client.execute_command('BF.INFO testBlocked') == bf_info_result_1
+        assert self.client.execute_command('BF.INFO testBlocked LAYOUT') == b'blocked'
+        # DUMP payloads of blocked filters can be restored.
+        assert self.client.execute_command('RESTORE testBlockedRestored 0', dump) == b'OK'
+        assert self.client.execute_command('BF.MEXISTS testBlockedRestored item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert self.client.execute_command('BF.INFO testBlockedRestored LAYOUT') == b'blocked'
+
+    def test_hash_function_save_and_restore(self):
+        client = self.server.get_new_client()
+        assert client.execute_command('BF.RESERVE testXxHash 0.001 1000 HASH XXHASH3') == b'OK'
+        assert client.execute_command('BF.RESERVE testMurmur 0.001 1000 HASH MURMURHASH3 LAYOUT BLOCKED') == b'OK'
+        assert client.execute_command('BF.MADD testXxHash item1 item2 item3') == [1, 1, 1]
+        assert client.execute_command('BF.MADD testMurmur item1 item2 item3') == [1, 1, 1]
+        dump = client.execute_command('DUMP testXxHash')
+
+        # save rdb, restart sever
+        client.bgsave()
+        self.server.wait_for_save_done()
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        assert self.server.is_rdb_done_loading()
+
+        # verify restore results
+        assert self.client.execute_command('BF.MEXISTS testXxHash item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert self.client.execute_command('BF.MEXISTS testMurmur item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert self.client.execute_command('BF.INFO testXxHash HASH') == b'xxhash3'
+        assert self.client.execute_command('BF.INFO testMurmur HASH') == b'murmurhash3'
+        assert self.client.execute_command('BF.INFO testMurmur LAYOUT') == b'blocked'
+        assert self.client.execute_command('RESTORE testXxHashRestored 0', dump) == b'OK'
+        assert self.client.execute_command('BF.MEXISTS testXxHashRestored item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert self.client.execute_command('BF.INFO testXxHashRestored HASH') == b'xxhash3'
+
+    def test_large_capacity_save_and_restore(self):
+        client = self.server.get_new_client()
+        # A high false positive rate keeps the bitmap of a filter with more than u32::MAX items small.
+        assert client.execute_command('BF.RESERVE testLarge 0.99 5000000000 NONSCALING') == b'OK'
+        assert client.execute_command('BF.MADD testLarge item1 item2 item3') == [1, 1, 1]
+        dump = client.execute_command('DUMP testLarge')
+
+        # save rdb, restart sever
+        client.bgsave()
+        self.server.wait_for_save_done()
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        assert self.server.is_rdb_done_loading()
+
+        # verify restore results
+        assert self.client.execute_command('BF.INFO testLarge CAPACITY') == 5000000000
+        assert self.client.execute_command('BF.CARD testLarge') == 3
+        assert self.client.execute_command('RESTORE testLargeRestored 0', dump) == b'OK'
+        assert self.client.execute_command('BF.INFO testLargeRestored CAPACITY') == 5000000000
+
+    def test_aux_data_save_and_restore(self):
+        client = self.server.get_new_client()
+        assert client.execute_command('CONFIG SET bloom-expansion-rate 4') == b'OK'
+        assert client.execute_command('CONFIG SET bloom-hash-function xxhash3') == b'OK'
+        assert client.execute_command('BF.ADD testAux item1') == 1
+
+        # save rdb, restart sever
+        client.bgsave()
+        self.server.wait_for_save_done()
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        assert self.server.is_rdb_done_loading()
+
+        # verify restore results, and that the default configs in effect when saving the RDB are logged as they differ
+        assert self.client.execute_command('BF.EXISTS testAux item1') == 1
+        assert self.client.execute_command('BF.INFO testAux EXPANSION') == 4
+        assert self.client.execute_command('BF.INFO testAux HASH') == b'xxhash3'
+        assert self.server.verify_string_in_logfile('RDB was produced with bloom-expansion-rate 4, while the current value is 2')
+        assert self.server.verify_string_in_logfile('RDB was produced with bloom-hash-function xxhash3, while the current value is siphash')
+        assert not self.server.verify_string_in_logfile('RDB was produced with bloom-max-item-size')
+
+    def test_no_aux_data_without_bloom_objects(self):
+        client = self.server.get_new_client()
+        assert client.execute_command('SET testString value') == b'OK'
+        assert client.execute_command('BF.ADD testDeleted item1') == 1
+        assert client.execute_command('DEL testDeleted') == 1
+        # RDB files without bloom objects hold no aux data of the module, so servers without it can load them.
+        client.bgsave()
+        self.server.wait_for_save_done()
+        rdb_path = os.path.join(self.server.cwd, self.server.args['dbfilename'])
+        output = self.run_bloom_tool('valkey-bloom-inspect', rdb_path).decode()
+        assert 'module aux data' not in output
+        assert '1 keys, 0 bloom objects' in output
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        assert self.client.execute_command('GET testString') == b'value'
+
+    def test_restore_objects_of_build_tool(self):
+        client = self.server.get_new_client()
+        item_names = [b'item%d' % i for i in range(1, 1001)]
+        items = b''.join(item + b'\n' for item in item_names)
+        # The DUMP payload written by the tool is restored as the same object as the one the items are added to.
+        payload = self.run_bloom_tool('valkey-bloom-build', '--capacity', '100', '--error-rate', '0.01', input=items)
+        assert client.execute_command('RESTORE testBuilt 0', payload) == b'OK'
+        assert client.execute_command('BF.RESERVE testAdded 0.01 100') == b'OK'
+        client.execute_command('BF.MADD testAdded', *item_names)
+        assert client.execute_command('TYPE testBuilt') == b'bloomfltr'
+        assert client.execute_command('BF.INFO testBuilt') == client.execute_command('BF.INFO testAdded')
+        assert client.execute_command('BF.CARD testBuilt') == client.execute_command('BF.CARD testAdded')
+        assert client.execute_command('DUMP testBuilt') == client.execute_command('DUMP testAdded')
+        assert client.execute_command('BF.INFO testBuilt CAPACITY') == 1500
+        assert client.execute_command('BF.MEXISTS testBuilt', *item_names) == [1] * 1000
+        # Objects are also loaded by the commands written by the tool.
+        commands = self.run_bloom_tool('valkey-bloom-build', '--layout', 'blocked', '--hash', 'xxhash3', '--nonscaling',
+                                       '--capacity', '1000', '--format', 'loadchunk', '--key', 'testBuiltChunks', input=items)
+        assert self.send_piped_commands(client, commands, 2) == [b'OK', b'OK']
+        assert client.execute_command('BF.INFO testBuiltChunks LAYOUT') == b'blocked'
+        assert client.execute_command('BF.INFO testBuiltChunks HASH') == b'xxhash3'
+        assert client.execute_command('BF.INFO testBuiltChunks EXPANSION') is None
+        assert client.execute_command('BF.CARD testBuiltChunks') == 1000
+        assert client.execute_command('BF.MEXISTS testBuiltChunks item1 item500 item1000') == [1, 1, 1]
+        commands = self.run_bloom_tool('valkey-bloom-build', '--format', 'restore', '--key', 'testBuilt', '--replace', input=b'other\n')
+        assert self.send_piped_commands(client, commands, 1) == [b'OK']
+        assert client.execute_command('BF.CARD testBuilt') == 1
+
+    def test_inspect_dump_payloads_and_rdb_files(self):
+        client = self.server.get_new_client()
+        assert client.execute_command('BF.RESERVE testInspect 0.01 100 LAYOUT BLOCKED HASH XXHASH3') == b'OK'
+        self.add_items_till_capacity(client, 'testInspect', 150, 1, 'item')
+        assert client.execute_command('RESTORE testInspectRedisBloom 0', self.build_redisbloom_dump([b'item1', b'item2'])) == b'OK'
+        assert client.execute_command('SET testString value') == b'OK'
+        # The objects of DUMP payloads are printed with their filters.
+        output = self.run_bloom_tool('valkey-bloom-inspect', input=client.execute_command('DUMP testInspect')).decode()
+        assert 'value (bloomfltr, encoding version 4)' in output
+        assert 'layout blocked, hash xxhash3, error rate 0.01, expansion 2, 2 filters, capacity 300' in output
+        assert 'filter 0: capacity 100, items 100' in output and 'filter 1: capacity 200' in output
+        assert '1 keys, 1 bloom objects, 0 of which cannot be loaded or fail validation' in output
+        # The bloom objects of RDB files are printed, along with the aux data of the module.
+        client.bgsave()
+        self.server.wait_for_save_done()
+        rdb_path = os.path.join(self.server.cwd, self.server.args['dbfilename'])
+        output = self.run_bloom_tool('valkey-bloom-inspect', rdb_path).decode()
+        assert 'module aux data: module version 1, bloomfltr encoding version 4' in output
+        assert 'key testInspect (bloomfltr, encoding version 4)' in output
+        assert 'key testInspectRedisBloom (MBbloom--, encoding version 4)' in output
+        assert 'layout redisbloom, hash murmurhash64a' in output
+        assert '3 keys, 2 bloom objects, 0 of which cannot be loaded or fail validation' in output
+        # Objects which cannot be loaded are reported with the reason.
+        corrupted = bytearray(self.build_redisbloom_dump([b'item1']))
+        corrupted[10] ^= 1
+        output = self.run_bloom_tool('valkey-bloom-inspect', input=bytes(corrupted), check=False).decode()
+        assert 'error: the checksum of the DUMP payload does not match' in output
+
+    def test_redisbloom_save_and_restore(self):
+        client = self.server.get_new_client()
+        items = [b'item1', b'item2', b'item3']
+        # DUMP payloads produced by RedisBloom can be restored, both for 64 bit filters and for (older) 32 bit filters
+        # whose number of bits is rounded up to a power of two.
+        assert client.execute_command('RESTORE testRedisBloom 0', self.build_redisbloom_dump(items)) == b'OK'
+        assert client.execute_command('RESTORE testRedisBloom32 0', self.build_redisbloom_dump(items, force64=False, noround=False, expansion=0)) == b'OK'
+        assert client.execute_command('TYPE testRedisBloom') == b'MBbloom--'
+        assert client.execute_command('BF.MEXISTS testRedisBloom item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert client.execute_command('BF.MEXISTS testRedisBloom32 item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert client.execute_command('BF.CARD testRedisBloom') == 3
+        assert client.execute_command('BF.INFO testRedisBloom CAPACITY') == 1000
+        assert client.execute_command('BF.INFO testRedisBloom LAYOUT') == b'redisbloom'
+        assert client.execute_command('BF.INFO testRedisBloom HASH') == b'murmurhash64a'
+        assert client.execute_command('BF.INFO testRedisBloom32 HASH') == b'murmurhash2'
+        assert client.execute_command('BF.INFO testRedisBloom32 EXPANSION') is None
+        # Items are added with the RedisBloom hashing, and the object scales out like RedisBloom objects.
+        assert client.execute_command('BF.ADD testRedisBloom item4') == 1
+        self.add_items_till_capacity(client, 'testRedisBloom', 1500, 5, 'item')
+        assert client.execute_command('BF.INFO testRedisBloom FILTERS') == 2
+        bf_info_result_1 = client.execute_command('BF.INFO testRedisBloom')
+        dump = client.execute_command('DUMP testRedisBloom')
+
+        # save rdb, restart sever
+        client.bgsave()
+        self.server.wait_for_save_done()
+        self.server.restart(remove_rdb=False, remove_nodes_conf=False, connect_client=True)
+        assert self.server.is_alive()
+        assert self.server.is_rdb_done_loading()
+
+        # verify restore results, objects keep the RedisBloom data type and are saved in its format
+        assert self.client.execute_command('TYPE testRedisBloom') == b'MBbloom--'
+        assert self.client.execute_command('BF.INFO testRedisBloom') == bf_info_result_1
+        assert self.client.execute_command('BF.MEXISTS testRedisBloom item1 item2 item3 item4') == [1, 1, 1, 1]
+        assert self.client.execute_command('BF.MEXISTS testRedisBloom32 item1 item2 item3 item4') == [1, 1, 1, 0]
+        assert self.client.execute_command('RESTORE testRedisBloomRestored 0', dump) == b'OK'
+        assert self.client.execute_command('BF.INFO testRedisBloomRestored') == bf_info_result_1
+        # Rebuilt objects replace RedisBloom objects with objects of the native data type.
+        assert self.client.execute_command('BF.COMPACT testRedisBloom {testRedisBloom}tmp') == b'OK'
+        assert self.client.execute_command('BF.INFO {testRedisBloom}tmp LAYOUT') == b'redisbloom'
+        assert self.client.execute_command('BF.SWAP testRedisBloom {testRedisBloom}tmp') == b'OK'
+        assert self.client.execute_command('TYPE testRedisBloom') == b'bloomfltr'
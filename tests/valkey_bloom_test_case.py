@@ -4,6 +4,9 @@ from valkeytests.valkey_test_case import ValkeyTestCase
 from valkey import ResponseError
 import random
 import string
+import math
+import struct
+import subprocess
 
 class ValkeyBloomTestCaseBase(ValkeyTestCase):
 
@@ -139,3 +142,113 @@ class ValkeyBloomTestCaseBase(ValkeyTestCase):
             item_prefix,
         )
         self.fp_assert(error_count, num_operations, expected_fp_rate, fp_margin)
+
+    def redisbloom_hashes(self, item, force64=True):
+        """ Returns the two hashes from which RedisBloom derives the bit positions of an item, computed with
+        MurmurHash64A for filters created with the FORCE64 option and with MurmurHash2 otherwise.
+        """
+        def murmur_hash(data, seed, bits, m, r, word_size):
+            mask = (1 << bits) - 1
+            h = (seed ^ (len(data) * m if bits == 64 else len(data))) & mask
+            tail_start = len(data) - len(data) % word_size
+            for i in range(0, tail_start, word_size):
+                k = (int.from_bytes(data[i:i + word_size], 'little') * m) & mask
+                k = ((k ^ (k >> r)) * m) & mask
+                h = ((h ^ k) * m) & mask if bits == 64 else ((h * m) & mask) ^ k
+            if tail_start < len(data):
+                h = ((h ^ int.from_bytes(data[tail_start:], 'little')) * m) & mask
+            if bits == 64:
+                h = ((h ^ (h >> r)) * m) & mask
+                return h ^ (h >> r)
+            h = ((h ^ (h >> 13)) * m) & mask
+            return h ^ (h >> 15)
+        if force64:
+            hash_one = murmur_hash(item, 0xc6a4a7935bd1e995, 64, 0xc6a4a7935bd1e995, 47, 8)
+            return hash_one, murmur_hash(item, hash_one, 64, 0xc6a4a7935bd1e995, 47, 8)
+        hash_one = murmur_hash(item, 0x9747b28c, 32, 0x5bd1e995, 24, 4)
+        return hash_one, murmur_hash(item, hash_one, 32, 0x5bd1e995, 24, 4)
+
+    def build_redisbloom_filter(self, items, capacity, fp_rate, force64, noround):
+        """ Returns the number of bits, number of hashes, bits per item, power of two of the number of bits and bitmap
+        of a RedisBloom filter holding the provided items, like RedisBloom sizes and fills it.
+        """
+        bits_per_item = -math.log(fp_rate) / (math.log(2) ** 2)
+        number_of_bits = max(1, int(capacity * bits_per_item))
+        n2 = 0
+        if not noround:
+            n2 = math.ceil(math.log2(number_of_bits))
+            number_of_bits = 1 << n2
+        number_of_hashes = math.ceil(math.log(2) * bits_per_item)
+        bitmap = bytearray((number_of_bits + 7) // 8)
+        for item in items:
+            hash_one, hash_two = self.redisbloom_hashes(item, force64)
+            for i in range(number_of_hashes):
+                # Like RedisBloom, positions are computed in 64 bit arithmetic, with the MurmurHash2 hashes widened to 64 bits.
+                position = ((hash_one + i * hash_two) & 0xffffffffffffffff) % number_of_bits
+                bitmap[position // 8] |= 1 << (position % 8)
+        return number_of_bits, number_of_hashes, bits_per_item, n2, bytes(bitmap)
+
+    def build_redisbloom_scandump(self, items, capacity=1000, fp_rate=0.01, force64=True, noround=True, expansion=2):
+        """ Builds the header and the single chunk replied by BF.SCANDUMP of RedisBloom for a single filter object
+        holding the provided items.
+        """
+        number_of_bits, number_of_hashes, bits_per_item, n2, bitmap = self.build_redisbloom_filter(items, capacity, fp_rate, force64, noround)
+        options = (1 if noround else 0) | (4 if force64 else 0) | (8 if expansion == 0 else 0)
+        header = struct.pack('<QIII', len(items), 1, options, max(expansion, 1))
+        header += struct.pack('<QQQddIQB', len(bitmap), number_of_bits, len(items), fp_rate, bits_per_item, number_of_hashes, capacity, n2)
+        return header, bitmap
+
+    def build_redisbloom_dump(self, items, capacity=1000, fp_rate=0.01, force64=True, noround=True, expansion=2):
+        """ Builds the DUMP payload of a single filter RedisBloom object (MBbloom-- data type, encoding version 4)
+        holding the provided items, like RedisBloom sizes and fills it.
+        """
+        def save_len(value):
+            if value < (1 << 6):
+                return bytes([value])
+            if value < (1 << 14):
+                return bytes([0x40 | (value >> 8), value & 0xff])
+            if value <= 0xffffffff:
+                return b'\x80' + value.to_bytes(4, 'big')
+            return b'\x81' + value.to_bytes(8, 'big')
+        def save_unsigned(value):
+            return save_len(2) + save_len(value)
+        def save_double(value):
+            return save_len(4) + struct.pack('<d', value)
+        def save_string(value):
+            return save_len(5) + save_len(len(value)) + value
+        def crc64(data):
+            crc = 0
+            for byte in data:
+                crc ^= byte
+                for _ in range(8):
+                    crc = (crc >> 1) ^ (0x95ac9329ac4bc9b5 if crc & 1 else 0)
+            return crc
+        number_of_bits, number_of_hashes, bits_per_item, n2, bitmap = self.build_redisbloom_filter(items, capacity, fp_rate, force64, noround)
+        options = (1 if noround else 0) | (4 if force64 else 0) | (8 if expansion == 0 else 0)
+        charset = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_'
+        module_id = 0
+        for char in 'MBbloom--':
+            module_id = (module_id << 6) | charset.index(char)
+        payload = b'\x07' + save_len((module_id << 10) | 4)
+        payload += save_unsigned(len(items)) + save_unsigned(1) + save_unsigned(options) + save_unsigned(max(expansion, 1))
+        payload += save_unsigned(capacity) + save_double(fp_rate) + save_unsigned(number_of_hashes) + save_double(bits_per_item)
+        payload += save_unsigned(number_of_bits) + save_unsigned(n2) + save_string(bitmap) + save_unsigned(len(items))
+        payload += save_len(0) + (11).to_bytes(2, 'little')
+        return payload + crc64(payload).to_bytes(8, 'little')
+
+    def run_bloom_tool(self, tool, *args, input=None, check=True):
+        """ Runs a command line tool of the valkey-bloom-core crate, which is built next to the module, and returns its
+        output. Unless check is False, the tool must exit successfully.
+        """
+        tool_path = os.path.join(os.path.dirname(os.getenv('MODULE_PATH')), tool)
+        return subprocess.run([tool_path, *args], input=input, capture_output=True, check=check).stdout
+
+    def send_piped_commands(self, client, commands, num_commands):
+        """ Sends commands in the RESP protocol as they are, like valkey-cli --pipe, and returns their replies.
+        """
+        connection = client.get_connection()
+        try:
+            connection.send_packed_command(commands)
+            return [connection.read_response() for _ in range(num_commands)]
+        finally:
+            client.release_connection(connection)